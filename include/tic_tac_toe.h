@@ -0,0 +1,97 @@
+/*
+ * C ABI of tic-tac-toe-learning-ai, built with `cargo build --release --lib
+ * --features ffi` into target/release/libtic_tac_toe_learning_ai.so (.dylib on
+ * macOS, .dll on Windows).
+ *
+ * Boards and agents are opaque handles owned by the caller and freed with
+ * ttt_board_free and ttt_agent_free. Fields are numbered 0 to 8 row by row and
+ * marks are 0 for empty, 1 for X and 2 for O. Every function that can fail
+ * returns a TttStatus and writes its result through an out pointer, which is
+ * left untouched on failure. No function panics or unwinds into the caller.
+ */
+#ifndef TIC_TAC_TOE_H
+#define TIC_TAC_TOE_H
+
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define TTT_ABI_VERSION 1
+
+#define TTT_EMPTY 0
+#define TTT_X 1
+#define TTT_O 2
+
+typedef enum TttStatus {
+    TTT_OK = 0,
+    TTT_NULL_POINTER = 1,
+    TTT_INVALID_FIELD = 2,
+    TTT_FIELD_TAKEN = 3,
+    TTT_GAME_OVER = 4,
+    TTT_INVALID_BOARD = 5,
+    TTT_INVALID_UTF8 = 6,
+    TTT_IO = 7,
+    TTT_PANIC = 8,
+    TTT_NO_MOVE = 9
+} TttStatus;
+
+typedef struct TttBoard TttBoard;
+typedef struct TttAgent TttAgent;
+
+/* TTT_ABI_VERSION of the loaded library, to compare with the header's. */
+uint32_t ttt_abi_version(void);
+
+/* Static description of a status, never to be freed. */
+const char *ttt_status_message(int status);
+
+/* Empty board, X moves first. */
+TttBoard *ttt_board_new(void);
+
+/* Board from nine 'X', 'O' and '.' characters row by row. */
+TttStatus ttt_board_from_code(const char *code, TttBoard **out);
+
+/* Frees a board, NULL is ignored. */
+void ttt_board_free(TttBoard *board);
+
+/* Plays field for the side to move. */
+TttStatus ttt_board_play(TttBoard *board, int field);
+
+/* Mark on field. */
+TttStatus ttt_board_field(const TttBoard *board, int field, int *out);
+
+/* Side to move, TTT_X or TTT_O. */
+TttStatus ttt_board_next_player(const TttBoard *board, int *out);
+
+/* TTT_X or TTT_O with three in a row, otherwise TTT_EMPTY. */
+TttStatus ttt_board_winner(const TttBoard *board, int *out);
+
+/* 1 if somebody has won or the board is full, otherwise 0. */
+TttStatus ttt_board_is_finished(const TttBoard *board, int *out);
+
+/* Writes nine characters and a NUL terminator into out. */
+TttStatus ttt_board_to_code(const TttBoard *board, char out[10]);
+
+/* Agent saved by the binary, a table or a neural agent. */
+TttStatus ttt_agent_load(const char *path, TttAgent **out);
+
+/* Frees an agent, NULL is ignored. */
+void ttt_agent_free(TttAgent *agent);
+
+/* Restarts the generator breaking ties between equally good moves. */
+TttStatus ttt_agent_reseed(TttAgent *agent, uint64_t seed);
+
+/* The agent's greedy move for the side to move, not played on the board.
+ * TTT_NO_MOVE if the agent has no move to an empty field. */
+TttStatus ttt_agent_move(TttAgent *agent, const TttBoard *board, int *out);
+
+/* Value of every field, NaN for fields the agent has no value for. */
+TttStatus ttt_agent_move_values(const TttAgent *agent, const TttBoard *board,
+                                double out[9]);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* TIC_TAC_TOE_H */
@@ -0,0 +1,152 @@
+use crate::agent::AgentConfig;
+use crate::board::{Board, FieldPosition};
+use crate::memory::BoardMemory;
+use crate::player::{Learner, Player};
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+
+/// Agent learning the value of the position reached after each of its moves.
+///
+/// Instead of a bias per board and action, the agent keeps one value per
+/// resulting board (the afterstate) and picks the move leading to the best
+/// known afterstate. Different move orders reaching the same position, or any of
+/// its symmetric versions, share one value.
+///
+/// At the end of a game the last afterstate moves towards the reward and every
+/// earlier one towards the value of the afterstate that followed it.
+#[derive(Clone)]
+pub struct AfterstateAgent {
+    values: BoardMemory<f64>,
+    epsilon: f64,
+    epsilon_decay: f64,
+    min_epsilon: f64,
+    learning_rate: f64,
+    rng: StdRng,
+    /// Saved afterstates of the current game in the order they were reached.
+    recorded_afterstates: Vec<Board>,
+}
+
+impl AfterstateAgent {
+    pub fn new(config: AgentConfig, learning_rate: f64) -> Self {
+        Self::with_rng(config, learning_rate, StdRng::from_entropy())
+    }
+
+    pub fn new_seeded(config: AgentConfig, learning_rate: f64, seed: u64) -> Self {
+        Self::with_rng(config, learning_rate, StdRng::seed_from_u64(seed))
+    }
+
+    fn with_rng(config: AgentConfig, learning_rate: f64, rng: StdRng) -> Self {
+        Self {
+            values: BoardMemory::new(),
+            epsilon: config.epsilon,
+            epsilon_decay: config.epsilon_decay,
+            min_epsilon: config.min_epsilon,
+            learning_rate,
+            rng,
+            recorded_afterstates: Vec::with_capacity(5),
+        }
+    }
+
+    /// Returns every legal move with the value of the board it leads to,
+    /// `0.0` for boards the agent has never reached.
+    pub fn afterstate_values(&self, board: &Board) -> Vec<(FieldPosition, f64)> {
+        if board.is_finished() {
+            return Vec::new();
+        }
+        board
+            .get_empty_fields_pos()
+            .into_iter()
+            .map(|pos| {
+                let afterstate = afterstate(board, pos);
+                (pos, self.values.lookup(&afterstate).copied().unwrap_or(0.0))
+            })
+            .collect()
+    }
+
+    /// Picks a move with the highest afterstate value, breaking ties randomly.
+    fn best_move(&mut self, board: &Board) -> Option<FieldPosition> {
+        let values = self.afterstate_values(board);
+        let best = values
+            .iter()
+            .map(|&(_, value)| value)
+            .max_by(f64::total_cmp)?;
+        let best_moves: Vec<FieldPosition> = values
+            .into_iter()
+            .filter(|&(_, value)| value == best)
+            .map(|(pos, _)| pos)
+            .collect();
+        best_moves.choose(&mut self.rng).copied()
+    }
+
+    pub fn memorized_boards_count(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn epsilon(&self) -> f64 {
+        self.epsilon
+    }
+}
+
+fn afterstate(board: &Board, pos: FieldPosition) -> Board {
+    let mut next = board.clone();
+    next.play_move_at(pos);
+    next
+}
+
+impl Player for AfterstateAgent {
+    fn choose_move(&mut self, board: &Board) -> Option<FieldPosition> {
+        self.best_move(board)
+    }
+}
+
+impl Learner for AfterstateAgent {
+    fn play_training_move(&mut self, board: &Board) -> Option<FieldPosition> {
+        let pos = if self.rng.gen_bool(self.epsilon) {
+            board.get_empty_fields_pos().choose(&mut self.rng).copied()
+        } else {
+            self.best_move(board)
+        }?;
+        let (saved_board, _) = self
+            .values
+            .find_or_insert_with(&afterstate(board, pos), || 0.0);
+        self.recorded_afterstates.push(saved_board);
+        Some(pos)
+    }
+
+    fn give_feedback(&mut self, reward: i32) {
+        if reward > 0 {
+            self.epsilon = (self.epsilon * self.epsilon_decay).max(self.min_epsilon);
+        }
+        let mut target = reward as f64;
+        for saved_board in self.recorded_afterstates.drain(..).rev() {
+            let value = self.values.get_mut(&saved_board).unwrap();
+            *value += self.learning_rate * (target - *value);
+            target = *value;
+        }
+    }
+}
+
+#[test]
+fn afterstate_agent_test() {
+    use crate::board::FieldState;
+    let config = AgentConfig {
+        epsilon: 0.0,
+        ..Default::default()
+    };
+    let mut agent = AfterstateAgent::new_seeded(config, 0.5, 0);
+    let board = Board([
+        [FieldState::X, FieldState::X, FieldState::Empty],
+        [FieldState::O, FieldState::O, FieldState::Empty],
+        [FieldState::Empty, FieldState::Empty, FieldState::Empty],
+    ]);
+    let winning_move = FieldPosition::new(2, 0);
+    let mut winning_board = board.clone();
+    winning_board.play_move_at(winning_move);
+
+    // Reward the winning move, its value then also covers the mirrored position
+    agent.recorded_afterstates.push(winning_board.clone());
+    agent.values.insert(winning_board, 0.0);
+    agent.give_feedback(6);
+    assert_eq!(agent.choose_move(&board), Some(winning_move));
+    let mirrored = board.get_flipped_vertically();
+    assert_eq!(agent.choose_move(&mirrored), Some(FieldPosition::new(0, 0)));
+}
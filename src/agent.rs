@@ -1,78 +1,230 @@
 use crate::board::{Board, FieldPosition, FieldState};
-use hashbrown::HashMap;
-use rand::{rngs::ThreadRng, seq::SliceRandom, Rng};
+use crate::decision::{Decision, DecisionKind};
+use crate::memory::{BoardMemory, BoardTransformation};
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+use std::borrow::Cow;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
 
+/// Rewards handed to an agent at the end of a game.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rewards {
+    pub win: i32,
+    pub loss: i32,
+    pub draw: i32,
+}
+
+impl Default for Rewards {
+    fn default() -> Self {
+        Self {
+            win: 6,
+            loss: -3,
+            draw: 1,
+        }
+    }
+}
+
+/// Exploration settings of an agent.
+///
+/// `epsilon` is the starting exploration rate. Every rewarded game multiplies it
+/// by `epsilon_decay`, but it never drops below `min_epsilon`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AgentConfig {
+    pub epsilon: f64,
+    pub epsilon_decay: f64,
+    pub min_epsilon: f64,
+}
+
+impl Default for AgentConfig {
+    fn default() -> Self {
+        Self {
+            epsilon: 0.6,
+            epsilon_decay: 0.999,
+            min_epsilon: 0.1,
+        }
+    }
+}
+
+#[derive(Clone)]
 pub struct Agent {
-    board_memory: HashMap<Board, Vec<AgentAction>>,
+    board_memory: BoardMemory<Vec<AgentAction>>,
     epsilon: f64,
-    rng: ThreadRng,
+    epsilon_decay: f64,
+    min_epsilon: f64,
+    rng: StdRng,
     recorded_actions: Vec<RecordedAction>,
+    shared_table: bool,
+    lambda: f64,
 }
 
 impl Agent {
     pub fn new_blank(epsilon: f64) -> Self {
-        Self {
-            board_memory: HashMap::new(),
+        Self::new(AgentConfig {
             epsilon,
-            rng: rand::thread_rng(),
+            ..Default::default()
+        })
+    }
+
+    pub fn new(config: AgentConfig) -> Self {
+        Self::with_rng(config, StdRng::from_entropy())
+    }
+
+    /// Creates an agent whose exploration is reproducible for the given `seed`.
+    pub fn new_seeded(config: AgentConfig, seed: u64) -> Self {
+        Self::with_rng(config, StdRng::seed_from_u64(seed))
+    }
+
+    fn with_rng(config: AgentConfig, rng: StdRng) -> Self {
+        Self {
+            board_memory: BoardMemory::new(),
+            epsilon: config.epsilon,
+            epsilon_decay: config.epsilon_decay,
+            min_epsilon: config.min_epsilon,
+            rng,
             recorded_actions: Vec::with_capacity(9),
+            shared_table: false,
+            lambda: 1.0,
+        }
+    }
+
+    /// Makes the agent learn a single table for both sides.
+    ///
+    /// Boards are looked at from the side to move: when O is to move, X and O are
+    /// swapped before the lookup, so "X" always means the agent's own marks. Both
+    /// players' moves of a self-play game then update the same table, see
+    /// [`Agent::give_feedback_per_side`].
+    pub fn with_shared_table(mut self) -> Self {
+        self.shared_table = true;
+        self
+    }
+
+    pub fn has_shared_table(&self) -> bool {
+        self.shared_table
+    }
+
+    /// Decays the credit for the result backward through the game, like the
+    /// eligibility traces of TD(λ).
+    ///
+    /// The last move of a side gets the full reward, the move before it `lambda`
+    /// times the reward, the one before that `lambda` squared times the reward and
+    /// so on, rounded to whole biases. `lambda = 1` rewards every move alike, which
+    /// is the default, and `lambda = 0` only rewards the last move. A board never
+    /// comes up twice in a game, so accumulating and replacing traces agree.
+    pub fn with_traces(mut self, lambda: f64) -> Self {
+        self.lambda = lambda.clamp(0.0, 1.0);
+        self
+    }
+
+    pub fn lambda(&self) -> f64 {
+        self.lambda
+    }
+
+    /// Returns the board the way this agent's memory sees it.
+    fn view<'a>(&self, board: &'a Board) -> Cow<'a, Board> {
+        if self.shared_table && board.next_player() == FieldState::O {
+            Cow::Owned(board.get_color_swapped())
+        } else {
+            Cow::Borrowed(board)
         }
     }
 
     pub fn get_actions_from_board(&self, board: &Board) -> Option<Vec<AgentAction>> {
-        let (saved_board, transformation) = match self.get_saved_board(board) {
-            Some(s) => s,
-            None => return None,
-        };
+        let (saved_board, transformation) = self.get_saved_board(&self.view(board))?;
         self.board_memory.get(&saved_board).map(|actions| {
             actions
                 .iter()
                 .map(|action| AgentAction {
                     field_pos: transformation.pos_to_original(action.field_pos),
-                    bias: action.bias,
+                    ..*action
                 })
                 .collect()
         })
     }
 
     pub fn play_greedy_exploration(&mut self, board: &Board) -> Option<FieldPosition> {
-        let empty_field_count = board.field_state_count(FieldState::Empty);
-        if empty_field_count == 0 {
-            return None;
-        } else if empty_field_count == 1 {
-            return Some(board.get_empty_fields_pos()[0]);
+        self.decide(board).map(|decision| decision.chosen)
+    }
+
+    /// Picks a training move like [`Agent::play_greedy_exploration`] and reports
+    /// how it came to it.
+    pub fn decide(&mut self, board: &Board) -> Option<Decision> {
+        let empty_fields = board.get_empty_fields_pos();
+        let side = board.next_player();
+        let view = self.view(board).into_owned();
+        if empty_fields.len() <= 1 {
+            // The last field is not worth remembering
+            let chosen = *empty_fields.first()?;
+            let (saved_board, transformation) = self
+                .get_saved_board(&view)
+                .unwrap_or((view, BoardTransformation::None));
+            return Some(Decision {
+                board: board.clone(),
+                chosen,
+                kind: DecisionKind::Forced,
+                epsilon: self.epsilon,
+                saved_board,
+                transformation,
+                candidates: self
+                    .get_actions_from_board(board)
+                    .unwrap_or_else(|| vec![AgentAction::new(chosen)]),
+            });
         }
-        let (saved_board, transformation) = match self.get_saved_board(board) {
+        let (saved_board, transformation) = match self.get_saved_board(&view) {
             Some(saved) => saved,
             None => {
                 // Save the board if not already saved
-                self.save_board(board.clone());
-                (board.clone(), BoardTransformation::None)
+                self.save_board(view.clone());
+                (view, BoardTransformation::None)
             }
         };
-        let action = if self.rng.gen_bool(self.epsilon) {
+        let explored = self.rng.gen_bool(self.epsilon);
+        let actions = self.board_memory.get(&saved_board).unwrap();
+        let action = if explored {
             // Choose a random action
-            self.board_memory
-                .get(&saved_board)
-                .unwrap()
-                .choose(&mut self.rng)
+            actions.choose(&mut self.rng)
         } else {
             // Choose the best action
-            self.board_memory.get(&saved_board).unwrap().iter().max()
-        };
-
-        let action = match action {
-            Some(&a) => a,
-            None => return None,
+            actions.iter().max()
         };
+        let action = *action?;
+        // Transform back to real board positions
+        let candidates = actions
+            .iter()
+            .map(|action| AgentAction {
+                field_pos: transformation.pos_to_original(action.field_pos),
+                ..*action
+            })
+            .collect();
 
         self.recorded_actions.push(RecordedAction {
             board: saved_board.clone(),
             action,
+            side,
         });
 
-        // Transform back to real board position
-        Some(transformation.pos_to_original(action.field_pos))
+        Some(Decision {
+            board: board.clone(),
+            chosen: transformation.pos_to_original(action.field_pos),
+            kind: if explored {
+                DecisionKind::Exploration
+            } else {
+                DecisionKind::Exploitation
+            },
+            epsilon: self.epsilon,
+            saved_board,
+            transformation,
+            candidates,
+        })
+    }
+
+    /// Plays the best known action without exploring or recording it.
+    /// Falls back to a random empty field on boards the agent has never seen.
+    pub fn play_greedy(&mut self, board: &Board) -> Option<FieldPosition> {
+        match self.get_actions_from_board(board) {
+            Some(actions) => actions.iter().max().map(|action| action.field_pos),
+            None => board.get_empty_fields_pos().choose(&mut self.rng).copied(),
+        }
     }
 
     fn save_board(&mut self, board: Board) {
@@ -85,50 +237,39 @@ impl Agent {
     }
 
     fn get_saved_board(&self, board: &Board) -> Option<(Board, BoardTransformation)> {
-        if self.board_memory.contains_key(board) {
-            return Some((board.clone(), BoardTransformation::None));
-        }
-        // Also check if identical transformed board is saved
-        // Rotated 90 degrees clockwise
-        let rotated = board.get_rotated_90_clockwise();
-        if self.board_memory.contains_key(&rotated) {
-            return Some((rotated, BoardTransformation::Rotated90CW));
-        }
-        // Rotated 180 degrees
-        let rotated = rotated.get_rotated_90_clockwise();
-        if self.board_memory.contains_key(&rotated) {
-            return Some((rotated, BoardTransformation::Rotated180));
-        }
-        // Rotated 270 clockwise
-        let rotated = rotated.get_rotated_90_clockwise();
-        if self.board_memory.contains_key(&rotated) {
-            return Some((rotated, BoardTransformation::Rotated90CCW));
-        }
-
-        let flipped = board.get_flipped_horizontally();
-        if self.board_memory.contains_key(&flipped) {
-            return Some((flipped, BoardTransformation::FlippedHorizontally));
-        }
-        let flipped = board.get_flipped_vertically();
-        if self.board_memory.contains_key(&flipped) {
-            return Some((flipped, BoardTransformation::FlippedVertically));
-        }
-        let flipped = board.get_flipped_diagonally_southwest_northeast();
-        if self.board_memory.contains_key(&flipped) {
-            return Some((flipped, BoardTransformation::FlippedDiagonallySWNE));
-        }
-        let flipped = board.get_flipped_diagonally_northwest_southeast();
-        if self.board_memory.contains_key(&flipped) {
-            return Some((flipped, BoardTransformation::FlippedDiagonallyNWSE));
-        }
-        None
+        self.board_memory.find(board)
     }
 
     pub fn give_feedback(&mut self, reward: i32) {
         if reward > 0 {
-            self.epsilon = (self.epsilon * 0.999).max(0.1);
+            self.decay_epsilon();
+        }
+        let recorded_actions: Vec<RecordedAction> =
+            self.recorded_actions.drain(..).collect();
+        self.reward_actions(&recorded_actions, reward);
+    }
+
+    /// Rewards the actions recorded for X and for O separately, for an agent
+    /// which played both sides of a game. Epsilon decays at most once per game.
+    pub fn give_feedback_per_side(&mut self, cross_reward: i32, circle_reward: i32) {
+        if cross_reward > 0 || circle_reward > 0 {
+            self.decay_epsilon();
         }
-        for recorded_action in self.recorded_actions.drain(..) {
+        let (cross_actions, circle_actions): (Vec<_>, Vec<_>) = self
+            .recorded_actions
+            .drain(..)
+            .partition(|recorded_action| recorded_action.side == FieldState::X);
+        self.reward_actions(&cross_actions, cross_reward);
+        self.reward_actions(&circle_actions, circle_reward);
+    }
+
+    fn decay_epsilon(&mut self) {
+        self.epsilon = (self.epsilon * self.epsilon_decay).max(self.min_epsilon);
+    }
+
+    fn reward_actions(&mut self, recorded_actions: &[RecordedAction], reward: i32) {
+        let mut trace = 1.0;
+        for recorded_action in recorded_actions.iter().rev() {
             let board = &recorded_action.board;
             let action = &recorded_action.action;
             let saved_action = self
@@ -138,8 +279,186 @@ impl Agent {
                 .iter_mut()
                 .find(|saved_action| saved_action.field_pos == action.field_pos)
                 .unwrap();
-            saved_action.give_feedback(reward);
+            saved_action.give_feedback((reward as f64 * trace).round() as i32);
+            saved_action.visits += 1;
+            trace *= self.lambda;
+        }
+    }
+
+    /// Adds the memory of `other` to this agent, summing biases of boards both know.
+    ///
+    /// Boards with X to move never appear in the memory of an O agent, so merging
+    /// an X and an O agent gives a single agent which can play both sides.
+    ///
+    /// # Panics
+    ///
+    /// Panics if only one of the agents uses a shared table, see
+    /// [`Agent::with_shared_table`].
+    pub fn merge(&mut self, other: &Agent) {
+        assert_eq!(
+            self.shared_table, other.shared_table,
+            "cannot merge a shared table with a per-side table"
+        );
+        for (board, other_actions) in other.board_memory.iter() {
+            let (saved_board, transformation) = match self.get_saved_board(board) {
+                Some(saved) => saved,
+                None => {
+                    self.board_memory
+                        .insert(board.clone(), other_actions.clone());
+                    continue;
+                }
+            };
+            let actions = self.board_memory.get_mut(&saved_board).unwrap();
+            for other_action in other_actions {
+                let pos = transformation.pos_to_saved(other_action.field_pos);
+                if let Some(action) = actions.iter_mut().find(|a| a.field_pos == pos) {
+                    action.give_feedback(other_action.bias);
+                    action.visits += other_action.visits;
+                }
+            }
+        }
+    }
+
+    /// Restarts the random number generator from `seed`.
+    pub fn reseed(&mut self, seed: u64) {
+        self.rng = StdRng::seed_from_u64(seed);
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write_to(&mut writer)?;
+        writer.flush()
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Self::read_from(BufReader::new(File::open(path)?))
+    }
+
+    /// Writes the agent as text: a header line with the exploration settings,
+    /// followed by the trace decay if it is below 1 and ending in `shared` for
+    /// shared tables, followed by one line per memorized
+    /// board, `<board> <x>,<y>:<bias>:<visits> ...`.
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        write!(
+            writer,
+            "agent {} {} {}",
+            self.epsilon, self.epsilon_decay, self.min_epsilon
+        )?;
+        if self.lambda < 1.0 {
+            write!(writer, " {}", self.lambda)?;
+        }
+        if self.shared_table {
+            write!(writer, " shared")?;
+        }
+        writeln!(writer)?;
+        let mut boards: Vec<(String, &Vec<AgentAction>)> = self
+            .board_memory
+            .iter()
+            .map(|(board, actions)| (board.to_code(), actions))
+            .collect();
+        boards.sort_by(|a, b| a.0.cmp(&b.0));
+        for (code, actions) in boards {
+            write!(writer, "{}", code)?;
+            for action in actions {
+                write!(
+                    writer,
+                    " {},{}:{}:{}",
+                    action.field_pos.x, action.field_pos.y, action.bias, action.visits
+                )?;
+            }
+            writeln!(writer)?;
+        }
+        Ok(())
+    }
+
+    pub fn read_from<R: BufRead>(reader: R) -> io::Result<Self> {
+        let invalid = |line: usize, msg: &str| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("line {}: {}", line + 1, msg),
+            )
+        };
+        let mut lines = reader.lines().enumerate();
+        let header = match lines.next() {
+            Some((_, line)) => line?,
+            None => return Err(invalid(0, "missing agent header")),
+        };
+        let mut settings: Vec<&str> = match header.strip_prefix("agent ") {
+            Some(values) => values.split_whitespace().collect(),
+            None => return Err(invalid(0, "missing agent header")),
+        };
+        let shared_table = settings.last() == Some(&"shared");
+        if shared_table {
+            settings.pop();
         }
+        let config: Vec<f64> = settings
+            .into_iter()
+            .map(|value| value.parse().map_err(|_| invalid(0, "invalid number")))
+            .collect::<io::Result<_>>()?;
+        let is_rate = |rate: f64| (0.0..=1.0).contains(&rate);
+        let (exploration, lambda) = match config.as_slice() {
+            [exploration @ .., lambda] if config.len() == 4 => (exploration, *lambda),
+            exploration => (exploration, 1.0),
+        };
+        if !is_rate(lambda) {
+            return Err(invalid(0, "trace decay outside 0 to 1"));
+        }
+        let mut agent = match exploration {
+            &[epsilon, epsilon_decay, min_epsilon]
+                if is_rate(epsilon) && is_rate(epsilon_decay) && is_rate(min_epsilon) =>
+            {
+                Self::new(AgentConfig {
+                    epsilon,
+                    epsilon_decay,
+                    min_epsilon,
+                })
+            }
+            [_, _, _] => return Err(invalid(0, "exploration rate outside 0 to 1")),
+            _ => return Err(invalid(0, "expected three exploration settings")),
+        };
+        agent.lambda = lambda;
+        agent.shared_table = shared_table;
+        for (i, line) in lines {
+            let line = line?;
+            let mut parts = line.split_whitespace();
+            let board = match parts.next() {
+                Some(code) => {
+                    Board::from_code(code).ok_or_else(|| invalid(i, "invalid board"))?
+                }
+                None => continue,
+            };
+            let actions = parts
+                .map(|action| {
+                    let parse_action = || -> Option<AgentAction> {
+                        let (pos, counts) = action.split_once(':')?;
+                        // Files written before visits were counted have none
+                        let (bias, visits) =
+                            counts.split_once(':').unwrap_or((counts, "0"));
+                        let (x, y) = pos.split_once(',')?;
+                        let field_pos =
+                            FieldPosition::new(x.parse().ok()?, y.parse().ok()?);
+                        // Only empty fields of the board can be played
+                        if field_pos.x > 2
+                            || field_pos.y > 2
+                            || board.0[field_pos.y][field_pos.x] != FieldState::Empty
+                        {
+                            return None;
+                        }
+                        Some(AgentAction {
+                            field_pos,
+                            bias: bias.parse().ok()?,
+                            visits: visits.parse().ok()?,
+                        })
+                    };
+                    parse_action().ok_or_else(|| invalid(i, "invalid action"))
+                })
+                .collect::<io::Result<Vec<_>>>()?;
+            if actions.is_empty() {
+                return Err(invalid(i, "board without actions"));
+            }
+            agent.board_memory.insert(board, actions);
+        }
+        Ok(agent)
     }
 
     pub fn memorized_boards_count(&self) -> usize {
@@ -149,17 +468,32 @@ impl Agent {
     pub fn epsilon(&self) -> f64 {
         self.epsilon
     }
+
+    pub fn set_epsilon(&mut self, epsilon: f64) {
+        self.epsilon = epsilon;
+    }
+
+    /// Forgets the moves recorded since the last feedback without rewarding them.
+    pub fn discard_game(&mut self) {
+        self.recorded_actions.clear();
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct AgentAction {
     pub field_pos: FieldPosition,
     pub bias: i32,
+    /// Number of finished games the action was played in.
+    pub visits: u32,
 }
 
 impl AgentAction {
     pub fn new(field_pos: FieldPosition) -> Self {
-        Self { field_pos, bias: 0 }
+        Self {
+            field_pos,
+            bias: 0,
+            visits: 0,
+        }
     }
 
     pub fn give_feedback(&mut self, reward: i32) {
@@ -179,52 +513,112 @@ impl PartialOrd for AgentAction {
     }
 }
 
+#[derive(Clone)]
 struct RecordedAction {
     board: Board,
     action: AgentAction,
+    /// The player who took the action.
+    side: FieldState,
 }
 
-enum BoardTransformation {
-    None,
-    Rotated90CW,
-    Rotated180,
-    Rotated90CCW,
-    FlippedHorizontally,
-    FlippedVertically,
-    FlippedDiagonallySWNE,
-    FlippedDiagonallyNWSE,
+#[test]
+fn read_from_test() {
+    let agent =
+        Agent::read_from("agent 0.5 0.9 0.1\nX........ 1,1:4:2 2,2:-1:1\n".as_bytes())
+            .unwrap();
+    assert_eq!(agent.memorized_boards_count(), 1);
+    assert_eq!(agent.epsilon(), 0.5);
+
+    let read = |content: &str| Agent::read_from(content.as_bytes()).map(|_| ());
+    let kind = |content: &str| read(content).unwrap_err().kind();
+    assert_eq!(
+        kind("agent 0.5 0.9 0.1\nX........\n"),
+        io::ErrorKind::InvalidData
+    );
+    assert_eq!(
+        kind("agent 0.5 0.9 0.1\nX........ 0,0:4:2\n"),
+        io::ErrorKind::InvalidData
+    );
+    assert_eq!(kind("agent 1.5 0.9 0.1\n"), io::ErrorKind::InvalidData);
+    assert_eq!(kind("agent 0.5 0.9 -0.1\n"), io::ErrorKind::InvalidData);
+    assert_eq!(kind("agent 0.5 3 0.1\n"), io::ErrorKind::InvalidData);
+}
+
+#[test]
+fn shared_table_test() {
+    let config = AgentConfig {
+        epsilon: 0.0,
+        ..Default::default()
+    };
+    let mut agent = Agent::new_seeded(config, 0).with_shared_table();
+    // X opens and O answers from the same table
+    let mut board = Board::EMPTY;
+    let cross_move = agent.play_greedy_exploration(&board).unwrap();
+    board.play_move_at(cross_move);
+    let circle_board = board.clone();
+    let circle_move = agent.play_greedy_exploration(&board).unwrap();
+    agent.give_feedback_per_side(6, -3);
+
+    let bias = |agent: &Agent, board: &Board, pos: FieldPosition| {
+        let actions = agent.get_actions_from_board(board).unwrap();
+        actions
+            .iter()
+            .find(|action| action.field_pos == pos)
+            .unwrap()
+            .bias
+    };
+    assert_eq!(agent.memorized_boards_count(), 2);
+    assert_eq!(bias(&agent, &Board::EMPTY, cross_move), 6);
+    assert_eq!(bias(&agent, &circle_board, circle_move), -3);
+
+    // O's entry is saved under the color swapped board, seen as X
+    let mut saved = Vec::new();
+    agent.write_to(&mut saved).unwrap();
+    let saved = String::from_utf8(saved).unwrap().replacen(" shared", "", 1);
+    let per_side = Agent::read_from(saved.as_bytes()).unwrap();
+    let swapped = circle_board.get_color_swapped();
+    assert_eq!(bias(&per_side, &swapped, circle_move), -3);
+    assert!(per_side.get_actions_from_board(&circle_board).is_none());
 }
 
-impl BoardTransformation {
-    fn pos_to_original(&self, pos: FieldPosition) -> FieldPosition {
+#[test]
+fn traces_test() {
+    let config = AgentConfig {
+        epsilon: 0.0,
+        ..Default::default()
+    };
+    // X plays three moves of a game it wins, returns the bias of each
+    let biases = |lambda| {
+        let mut agent = Agent::new_seeded(config, 0).with_traces(lambda);
         let mut board = Board::EMPTY;
-        board.0[pos.y][pos.x] = FieldState::X;
-        let transformed_board = match self {
-            BoardTransformation::None => return pos,
-            BoardTransformation::Rotated90CW => board
-                .get_rotated_90_clockwise()
-                .get_rotated_90_clockwise()
-                .get_rotated_90_clockwise(),
-            BoardTransformation::Rotated180 => {
-                board.get_rotated_90_clockwise().get_rotated_90_clockwise()
-            }
-            BoardTransformation::Rotated90CCW => board.get_rotated_90_clockwise(),
-            BoardTransformation::FlippedHorizontally => board.get_flipped_horizontally(),
-            BoardTransformation::FlippedVertically => board.get_flipped_vertically(),
-            BoardTransformation::FlippedDiagonallySWNE => {
-                board.get_flipped_diagonally_southwest_northeast()
-            }
-            BoardTransformation::FlippedDiagonallyNWSE => {
-                board.get_flipped_diagonally_northwest_southeast()
-            }
-        };
-        for y in 0..3 {
-            for x in 0..3 {
-                if transformed_board.0[y][x] == FieldState::X {
-                    return FieldPosition::new(x, y);
-                }
-            }
+        let mut played = Vec::new();
+        for _ in 0..3 {
+            let pos = agent.play_greedy_exploration(&board).unwrap();
+            played.push((board.clone(), pos));
+            board.play_move_at(pos);
+            board.play_move_at(board.get_empty_fields_pos()[0]);
         }
-        unreachable!()
-    }
+        agent.give_feedback(8);
+        played
+            .iter()
+            .map(|(board, pos)| {
+                let actions = agent.get_actions_from_board(board).unwrap();
+                actions
+                    .iter()
+                    .find(|action| action.field_pos == *pos)
+                    .unwrap()
+                    .bias
+            })
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(biases(1.0), vec![8, 8, 8]);
+    assert_eq!(biases(0.5), vec![2, 4, 8]);
+    assert_eq!(biases(0.0), vec![0, 0, 8]);
+
+    let agent = Agent::new_blank(0.5).with_traces(0.5);
+    let mut saved = Vec::new();
+    agent.write_to(&mut saved).unwrap();
+    assert!(saved.starts_with(b"agent 0.5 0.999 0.1 0.5\n"));
+    assert_eq!(Agent::read_from(saved.as_slice()).unwrap().lambda(), 0.5);
+    assert!(Agent::read_from("agent 0.5 0.9 0.1 2\n".as_bytes()).is_err());
 }
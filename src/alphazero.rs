@@ -0,0 +1,455 @@
+use crate::board::{Board, FieldPosition, FieldState};
+use crate::evaluation;
+use crate::memory::BoardMemory;
+use crate::minimax::PerfectPlayer;
+use crate::mlp::Mlp;
+use crate::neural::{self, INPUTS};
+use crate::player::Player;
+use crate::policy::{softmax, PolicyModel};
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+use std::collections::VecDeque;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AlphaZeroConfig {
+    pub model: PolicyModel,
+    /// Search iterations per move.
+    pub simulations: usize,
+    /// Weight of the prior against the search values when selecting moves.
+    pub c_puct: f64,
+    /// Share of random noise mixed into the root priors during self-play.
+    pub root_noise: f64,
+    /// Self-play moves sampled from the visit counts at the start of every game,
+    /// later moves take the most visited one.
+    pub sampled_moves: usize,
+    pub iterations: usize,
+    pub games_per_iteration: usize,
+    /// Passes over the self-play positions after every iteration.
+    pub epochs: usize,
+    /// Positions of this many latest iterations are trained on.
+    pub window: usize,
+    /// Games per side against the perfect player after every iteration.
+    pub evaluation_games: usize,
+    pub seed: u64,
+}
+
+impl Default for AlphaZeroConfig {
+    fn default() -> Self {
+        Self {
+            model: PolicyModel::Table { learning_rate: 0.3 },
+            simulations: 50,
+            c_puct: 1.5,
+            root_noise: 0.25,
+            sampled_moves: 4,
+            iterations: 20,
+            games_per_iteration: 50,
+            epochs: 4,
+            window: 4,
+            evaluation_games: 10,
+            seed: 0,
+        }
+    }
+}
+
+/// Learned move priors and board values guiding the search.
+#[derive(Clone)]
+enum Evaluator {
+    Table {
+        learning_rate: f64,
+        /// Priors by saved position and the value of the saved board.
+        entries: BoardMemory<(Vec<(FieldPosition, f64)>, f64)>,
+    },
+    Neural {
+        policy: Mlp,
+        value: Mlp,
+    },
+}
+
+impl Evaluator {
+    fn new(model: PolicyModel, rng: &mut StdRng) -> Self {
+        match model {
+            PolicyModel::Table { learning_rate } => Evaluator::Table {
+                learning_rate,
+                entries: BoardMemory::new(),
+            },
+            PolicyModel::Neural {
+                hidden_units,
+                optimizer,
+            } => Evaluator::Neural {
+                policy: Mlp::new(&[INPUTS, hidden_units, 9], optimizer, rng),
+                value: Mlp::new(&[INPUTS, hidden_units, 1], optimizer, rng),
+            },
+        }
+    }
+
+    /// Returns the prior of every legal move and the value of the board for the
+    /// player to move, between -1 for a loss and 1 for a win.
+    fn evaluate(&self, board: &Board) -> (Vec<(FieldPosition, f64)>, f64) {
+        let moves = board.get_empty_fields_pos();
+        match self {
+            Evaluator::Table { entries, .. } => match entries.find(board) {
+                Some((saved_board, transformation)) => {
+                    let (priors, value) = entries.get(&saved_board).unwrap();
+                    let priors = priors
+                        .iter()
+                        .map(|&(pos, prior)| (transformation.pos_to_original(pos), prior))
+                        .collect();
+                    (priors, *value)
+                }
+                None => {
+                    let uniform = 1.0 / moves.len() as f64;
+                    (moves.into_iter().map(|pos| (pos, uniform)).collect(), 0.0)
+                }
+            },
+            Evaluator::Neural { policy, value } => {
+                let input = neural::encode(board);
+                let logits = policy.forward(&input);
+                let logits: Vec<(FieldPosition, f64)> = moves
+                    .into_iter()
+                    .map(|pos| (pos, logits[neural::field_index(pos)]))
+                    .collect();
+                let value = value.forward(&input)[0].clamp(-1.0, 1.0);
+                (softmax(&logits), value)
+            }
+        }
+    }
+
+    /// Moves the priors of a board towards the search's visit distribution and
+    /// its value towards the game's outcome for the player to move.
+    fn train(&mut self, board: &Board, targets: &[(FieldPosition, f64)], outcome: f64) {
+        let (priors, _) = self.evaluate(board);
+        match self {
+            Evaluator::Table {
+                learning_rate,
+                entries,
+            } => {
+                let (saved_board, transformation) =
+                    entries.find_or_insert_with(board, || (priors.clone(), 0.0));
+                let (saved_priors, value) = entries.get_mut(&saved_board).unwrap();
+                for &(pos, target) in targets {
+                    let saved_pos = transformation.pos_to_saved(pos);
+                    let (_, prior) = saved_priors
+                        .iter_mut()
+                        .find(|(pos, _)| *pos == saved_pos)
+                        .unwrap();
+                    *prior += *learning_rate * (target - *prior);
+                }
+                *value += *learning_rate * (outcome - *value);
+            }
+            Evaluator::Neural { policy, value } => {
+                let input = neural::encode(board);
+                // Gradient of the cross entropy between the targets and the softmax
+                let mut gradient = vec![0.0; 9];
+                for &(pos, prior) in &priors {
+                    gradient[neural::field_index(pos)] += prior;
+                }
+                for &(pos, target) in targets {
+                    gradient[neural::field_index(pos)] -= target;
+                }
+                policy.train_gradient(&input, &gradient);
+                value.train(&input, 0, outcome);
+            }
+        }
+    }
+}
+
+/// Self-play position with its training targets.
+struct TrainingPosition {
+    board: Board,
+    /// Share of the search's visits of every legal move.
+    targets: Vec<(FieldPosition, f64)>,
+    /// Outcome of the game for the player to move.
+    outcome: f64,
+}
+
+struct Node {
+    board: Board,
+    field_pos: Option<FieldPosition>,
+    prior: f64,
+    children: Vec<usize>,
+    expanded: bool,
+    visits: u32,
+    /// Sum of values from the view of the player who moved into this node.
+    value_sum: f64,
+}
+
+impl Node {
+    fn new(board: Board, field_pos: Option<FieldPosition>, prior: f64) -> Self {
+        Self {
+            board,
+            field_pos,
+            prior,
+            children: Vec::new(),
+            expanded: false,
+            visits: 0,
+            value_sum: 0.0,
+        }
+    }
+}
+
+/// Player searching with Monte Carlo tree search guided by learned move priors
+/// and board values instead of random playouts (PUCT, as in AlphaZero).
+///
+/// Training alternates self-play, where every position is searched and the
+/// visit counts of its moves become the targets for the priors, with fitting the
+/// priors and values to those targets and the games' outcomes. See
+/// [`run_alphazero`].
+#[derive(Clone)]
+pub struct AlphaZeroAgent {
+    evaluator: Evaluator,
+    simulations: usize,
+    c_puct: f64,
+    rng: StdRng,
+}
+
+impl AlphaZeroAgent {
+    pub fn new(config: &AlphaZeroConfig) -> Self {
+        Self::with_rng(config, StdRng::from_entropy())
+    }
+
+    pub fn new_seeded(config: &AlphaZeroConfig, seed: u64) -> Self {
+        Self::with_rng(config, StdRng::seed_from_u64(seed))
+    }
+
+    fn with_rng(config: &AlphaZeroConfig, mut rng: StdRng) -> Self {
+        Self {
+            evaluator: Evaluator::new(config.model, &mut rng),
+            simulations: config.simulations,
+            c_puct: config.c_puct,
+            rng,
+        }
+    }
+
+    /// Learned prior of every legal move and value of the board for the player
+    /// to move, without searching.
+    pub fn evaluate(&self, board: &Board) -> (Vec<(FieldPosition, f64)>, f64) {
+        self.evaluator.evaluate(board)
+    }
+
+    /// Searches the board and returns how often each legal move was visited.
+    /// `root_noise` mixes that share of random noise into the root priors.
+    pub fn visit_counts(
+        &mut self,
+        board: &Board,
+        root_noise: f64,
+    ) -> Vec<(FieldPosition, u32)> {
+        if board.is_finished() {
+            return Vec::new();
+        }
+        let mut tree = vec![Node::new(board.clone(), None, 1.0)];
+        self.expand(&mut tree, 0);
+        if root_noise > 0.0 {
+            let noise: Vec<f64> = tree[0]
+                .children
+                .iter()
+                .map(|_| self.rng.gen::<f64>())
+                .collect();
+            let sum: f64 = noise.iter().sum();
+            for (i, &child) in tree[0].children.clone().iter().enumerate() {
+                let prior = &mut tree[child].prior;
+                *prior = (1.0 - root_noise) * *prior + root_noise * noise[i] / sum;
+            }
+        }
+
+        for _ in 0..self.simulations.max(1) {
+            let mut path = vec![0];
+            let mut node = 0;
+            while tree[node].expanded && !tree[node].children.is_empty() {
+                node = self.select_child(&tree, node);
+                path.push(node);
+            }
+            // Value for the player to move at the leaf
+            let value = if tree[node].board.find_winner() != FieldState::Empty {
+                -1.0
+            } else if tree[node].board.is_finished() {
+                0.0
+            } else {
+                self.expand(&mut tree, node)
+            };
+            // Every node stores values from the view of the player who moved into it
+            let mut value = -value;
+            for &i in path.iter().rev() {
+                tree[i].visits += 1;
+                tree[i].value_sum += value;
+                value = -value;
+            }
+        }
+        tree[0]
+            .children
+            .iter()
+            .map(|&child| (tree[child].field_pos.unwrap(), tree[child].visits))
+            .collect()
+    }
+
+    /// Adds the children of a node with their priors and returns the node's value.
+    fn expand(&self, tree: &mut Vec<Node>, node: usize) -> f64 {
+        let (priors, value) = self.evaluator.evaluate(&tree[node].board);
+        for (pos, prior) in priors {
+            let mut board = tree[node].board.clone();
+            board.play_move_at(pos);
+            tree.push(Node::new(board, Some(pos), prior));
+            let child = tree.len() - 1;
+            tree[node].children.push(child);
+        }
+        tree[node].expanded = true;
+        value
+    }
+
+    fn select_child(&self, tree: &[Node], node: usize) -> usize {
+        let parent_visits = (tree[node].visits as f64).sqrt();
+        let puct = |child: usize| {
+            let child = &tree[child];
+            let q = if child.visits > 0 {
+                child.value_sum / child.visits as f64
+            } else {
+                0.0
+            };
+            q + self.c_puct * child.prior * parent_visits / (1.0 + child.visits as f64)
+        };
+        *tree[node]
+            .children
+            .iter()
+            .max_by(|&&a, &&b| puct(a).total_cmp(&puct(b)))
+            .unwrap()
+    }
+
+    /// Plays one self-play game and returns its positions.
+    fn self_play_game(&mut self, config: &AlphaZeroConfig) -> Vec<TrainingPosition> {
+        let mut positions = Vec::new();
+        let mut board = Board::EMPTY;
+        while !board.is_finished() {
+            let visits = self.visit_counts(&board, config.root_noise);
+            let total: u32 = visits.iter().map(|&(_, visits)| visits).sum();
+            let targets: Vec<(FieldPosition, f64)> = visits
+                .iter()
+                .map(|&(pos, visits)| (pos, visits as f64 / total as f64))
+                .collect();
+            let pos = if positions.len() < config.sampled_moves {
+                targets
+                    .choose_weighted(&mut self.rng, |&(_, share)| share)
+                    .unwrap()
+                    .0
+            } else {
+                most_visited(&visits, &mut self.rng).unwrap()
+            };
+            positions.push((board.clone(), targets));
+            board.play_move_at(pos);
+        }
+        let winner = board.find_winner();
+        positions
+            .into_iter()
+            .map(|(board, targets)| {
+                let outcome = if winner == FieldState::Empty {
+                    0.0
+                } else if winner == board.next_player() {
+                    1.0
+                } else {
+                    -1.0
+                };
+                TrainingPosition {
+                    board,
+                    targets,
+                    outcome,
+                }
+            })
+            .collect()
+    }
+}
+
+fn most_visited(
+    visits: &[(FieldPosition, u32)],
+    rng: &mut StdRng,
+) -> Option<FieldPosition> {
+    let best = visits.iter().map(|&(_, visits)| visits).max()?;
+    let best_moves: Vec<FieldPosition> = visits
+        .iter()
+        .filter(|&&(_, visits)| visits == best)
+        .map(|&(pos, _)| pos)
+        .collect();
+    best_moves.choose(rng).copied()
+}
+
+/// Searches without noise and plays the most visited move.
+impl Player for AlphaZeroAgent {
+    fn choose_move(&mut self, board: &Board) -> Option<FieldPosition> {
+        let visits = self.visit_counts(board, 0.0);
+        most_visited(&visits, &mut self.rng)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct IterationReport {
+    pub iteration: usize,
+    /// Self-play positions trained on this iteration.
+    pub positions: usize,
+    /// Share of non-lost games against the perfect player after the iteration.
+    pub strength: f64,
+}
+
+/// Trains `agent` from scratch by self-play, measuring its strength against the
+/// perfect player after every iteration.
+pub fn run_alphazero<F>(
+    agent: &mut AlphaZeroAgent,
+    config: &AlphaZeroConfig,
+    mut on_iteration: F,
+) -> Vec<IterationReport>
+where
+    F: FnMut(&IterationReport),
+{
+    let mut rng = StdRng::seed_from_u64(config.seed);
+    let mut judge = PerfectPlayer::new_seeded(config.seed.wrapping_add(1));
+    let mut reports = Vec::with_capacity(config.iterations);
+    let mut window: VecDeque<Vec<TrainingPosition>> = VecDeque::new();
+    for iteration in 0..config.iterations {
+        let mut positions = Vec::new();
+        for _ in 0..config.games_per_iteration {
+            positions.extend(agent.self_play_game(config));
+        }
+        let new_positions = positions.len();
+        window.push_back(positions);
+        if window.len() > config.window.max(1) {
+            window.pop_front();
+        }
+        let mut positions: Vec<&TrainingPosition> = window.iter().flatten().collect();
+        for _ in 0..config.epochs {
+            positions.shuffle(&mut rng);
+            for position in &positions {
+                agent.evaluator.train(
+                    &position.board,
+                    &position.targets,
+                    position.outcome,
+                );
+            }
+        }
+        let result =
+            evaluation::evaluate_as_cross(agent, &mut judge, config.evaluation_games)
+                + evaluation::evaluate_as_circle(
+                    agent,
+                    &mut judge,
+                    config.evaluation_games,
+                );
+        let report = IterationReport {
+            iteration,
+            positions: new_positions,
+            strength: result.non_loss_rate(),
+        };
+        on_iteration(&report);
+        reports.push(report);
+    }
+    reports
+}
+
+#[test]
+fn alphazero_search_test() {
+    // Even an untrained agent finds a win in one with enough simulations
+    let config = AlphaZeroConfig {
+        simulations: 200,
+        ..Default::default()
+    };
+    let mut agent = AlphaZeroAgent::new_seeded(&config, 0);
+    let board = Board([
+        [FieldState::X, FieldState::X, FieldState::Empty],
+        [FieldState::O, FieldState::O, FieldState::Empty],
+        [FieldState::Empty, FieldState::Empty, FieldState::Empty],
+    ]);
+    assert_eq!(agent.choose_move(&board), Some(FieldPosition::new(2, 0)));
+}
@@ -0,0 +1,59 @@
+use tic_tac_toe_learning_ai::saved::SavedAgent;
+use tic_tac_toe_learning_ai::server::{self, Api};
+use tiny_http::Server;
+
+const USAGE: &str = "\
+Usage:
+    tic-tac-toe-server --agent <FILE> [OPTIONS]  answer move requests of a saved agent
+
+Options:
+    --agent <FILE>        saved agent to serve
+    --address <ADDR>      address to listen on                [default: 127.0.0.1:8080]
+    --seed <N>            seed breaking ties between moves, random if not given
+
+Endpoints (boards are `{\"board\": \"X...O....\"}`, row by row):
+    POST /move            the agent's move and the values of the moves it knows
+    POST /evaluate        the minimax value of the board and of every move
+    GET  /stats           kind of agent, memorized boards and exploration rate";
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Err(err) = run(&args) {
+        eprintln!("error: {}\n\n{}", err, USAGE);
+        std::process::exit(1);
+    }
+}
+
+fn run(args: &[String]) -> Result<(), String> {
+    let mut agent_path = None;
+    let mut address = "127.0.0.1:8080".to_string();
+    let mut seed = rand::random::<u64>();
+    let mut args = args.iter();
+    while let Some(option) = args.next() {
+        if matches!(option.as_str(), "help" | "--help" | "-h") {
+            println!("{}", USAGE);
+            return Ok(());
+        }
+        let value = args
+            .next()
+            .ok_or_else(|| format!("missing value for `{}`", option))?;
+        match option.as_str() {
+            "--agent" => agent_path = Some(value),
+            "--address" => address = value.clone(),
+            "--seed" => {
+                seed = value
+                    .parse()
+                    .map_err(|_| format!("invalid value `{}` for `{}`", value, option))?
+            }
+            _ => return Err(format!("unknown option `{}`", option)),
+        }
+    }
+    let path = agent_path.ok_or("missing `--agent`")?;
+    let failed = |err: std::io::Error| format!("failed to load `{}`: {}", path, err);
+    let mut agent = SavedAgent::load(path).map_err(failed)?;
+    agent.reseed(seed);
+
+    let server = Server::http(&address).map_err(|err| err.to_string())?;
+    eprintln!("Serving `{}` on http://{}", path, address);
+    server::serve(&mut Api::new(agent), &server).map_err(|err| err.to_string())
+}
@@ -21,6 +21,22 @@ impl Board {
         }
     }
 
+    /// Returns the player whose turn it is, [`FieldState::X`] always starts.
+    pub fn next_player(&self) -> FieldState {
+        if self.field_state_count(FieldState::X) == self.field_state_count(FieldState::O)
+        {
+            FieldState::X
+        } else {
+            FieldState::O
+        }
+    }
+
+    /// Returns `true` if somebody has won or there are no empty fields left.
+    pub fn is_finished(&self) -> bool {
+        self.find_winner() != FieldState::Empty
+            || self.field_state_count(FieldState::Empty) == 0
+    }
+
     pub fn field_state_count(&self, field_state: FieldState) -> usize {
         self.0
             .iter()
@@ -53,13 +69,13 @@ impl Board {
 
     pub fn get_flipped_vertically(&self) -> Self {
         let mut flip = self.clone();
-        flip.0.swap(0, 2);
+        flip.0.iter_mut().for_each(|row| row.swap(0, 2));
         flip
     }
 
     pub fn get_flipped_horizontally(&self) -> Self {
         let mut flip = self.clone();
-        flip.0.iter_mut().for_each(|row| row.swap(0, 2));
+        flip.0.swap(0, 2);
         flip
     }
 
@@ -83,6 +99,48 @@ impl Board {
         flip
     }
 
+    /// Encodes the board row by row as nine characters of `X`, `O` and `.`.
+    pub fn to_code(&self) -> String {
+        self.0
+            .iter()
+            .flat_map(|row| row.iter())
+            .map(|field| match field {
+                FieldState::Empty => '.',
+                FieldState::X => 'X',
+                FieldState::O => 'O',
+            })
+            .collect()
+    }
+
+    /// Decodes a board written by [`Board::to_code`].
+    pub fn from_code(code: &str) -> Option<Self> {
+        let fields: Vec<char> = code.chars().collect();
+        if fields.len() != 9 {
+            return None;
+        }
+        let mut board = Self::EMPTY;
+        for (i, field) in fields.into_iter().enumerate() {
+            board.0[i / 3][i % 3] = match field {
+                '.' => FieldState::Empty,
+                'X' | 'x' => FieldState::X,
+                'O' | 'o' => FieldState::O,
+                _ => return None,
+            };
+        }
+        Some(board)
+    }
+
+    /// Returns the board with every X replaced by O and every O by X.
+    pub fn get_color_swapped(&self) -> Self {
+        let mut swapped = self.clone();
+        swapped
+            .0
+            .iter_mut()
+            .flat_map(|row| row.iter_mut())
+            .for_each(|field| *field = field.opponent());
+        swapped
+    }
+
     pub fn find_winner(&self) -> FieldState {
         let is_all_same = |row: &[FieldState; 3]| -> bool {
             let first = row[0];
@@ -119,7 +177,18 @@ pub enum FieldState {
     O = 2,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd)]
+impl FieldState {
+    /// Returns the other player, [`FieldState::Empty`] stays empty.
+    pub fn opponent(&self) -> Self {
+        match self {
+            FieldState::Empty => FieldState::Empty,
+            FieldState::X => FieldState::O,
+            FieldState::O => FieldState::X,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Hash)]
 pub struct FieldPosition {
     pub x: usize,
     pub y: usize,
@@ -0,0 +1,238 @@
+use crate::agent::{Agent, Rewards};
+use crate::board::FieldState;
+use crate::evaluation::{self, EvaluationResult};
+use crate::minimax::{Difficulty, MinimaxPlayer, PerfectPlayer};
+use crate::player::{HeuristicPlayer, Player, RandomPlayer};
+use crate::training;
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+
+/// Kinds of sparring partners in the opponent pool.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Opponent {
+    Random,
+    Heuristic,
+    Scripted(Difficulty),
+    /// A frozen copy of the learner from an earlier round.
+    Snapshot,
+}
+
+impl std::fmt::Display for Opponent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Opponent::Random => write!(f, "random"),
+            Opponent::Heuristic => write!(f, "heuristic"),
+            Opponent::Scripted(difficulty) => write!(f, "{}", difficulty),
+            Opponent::Snapshot => write!(f, "snapshot"),
+        }
+    }
+}
+
+/// Opponent mix used while the learner's strength is at least `min_strength`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CurriculumStage {
+    pub min_strength: f64,
+    /// Opponents with their relative share of the training games.
+    pub mix: Vec<(Opponent, f64)>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CurriculumConfig {
+    /// Stages ordered by ascending `min_strength`.
+    pub stages: Vec<CurriculumStage>,
+    pub rounds: usize,
+    pub games_per_round: usize,
+    /// Games per side against the perfect player after every round.
+    pub evaluation_games: usize,
+    /// A snapshot of the learner joins the pool every this many rounds.
+    pub snapshot_every: usize,
+    /// The oldest snapshot is dropped once the pool holds more than this.
+    pub max_snapshots: usize,
+    pub rewards: Rewards,
+    pub seed: u64,
+}
+
+impl Default for CurriculumConfig {
+    fn default() -> Self {
+        Self {
+            stages: default_stages(),
+            rounds: 50,
+            games_per_round: 2_000,
+            evaluation_games: 50,
+            snapshot_every: 5,
+            max_snapshots: 5,
+            rewards: Rewards::default(),
+            seed: 0,
+        }
+    }
+}
+
+/// Starts with weak opponents and moves towards stronger ones and the learner's
+/// own snapshots as it stops losing against the perfect player.
+pub fn default_stages() -> Vec<CurriculumStage> {
+    use Opponent::*;
+    vec![
+        CurriculumStage {
+            min_strength: 0.0,
+            mix: vec![
+                (Random, 0.4),
+                (Heuristic, 0.3),
+                (Scripted(Difficulty::Easy), 0.3),
+            ],
+        },
+        CurriculumStage {
+            min_strength: 0.5,
+            mix: vec![
+                (Random, 0.1),
+                (Heuristic, 0.2),
+                (Scripted(Difficulty::Medium), 0.4),
+                (Snapshot, 0.3),
+            ],
+        },
+        CurriculumStage {
+            min_strength: 0.8,
+            mix: vec![
+                (Random, 0.1),
+                (Scripted(Difficulty::Medium), 0.2),
+                (Scripted(Difficulty::Hard), 0.4),
+                (Snapshot, 0.3),
+            ],
+        },
+        CurriculumStage {
+            min_strength: 0.95,
+            mix: vec![
+                (Scripted(Difficulty::Hard), 0.4),
+                (Scripted(Difficulty::Perfect), 0.2),
+                (Snapshot, 0.4),
+            ],
+        },
+    ]
+}
+
+impl CurriculumConfig {
+    /// Index of the stage used at the given strength.
+    pub fn stage_for(&self, strength: f64) -> usize {
+        self.stages
+            .iter()
+            .rposition(|stage| strength >= stage.min_strength)
+            .unwrap_or(0)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RoundReport {
+    pub round: usize,
+    /// Stage the round was trained with.
+    pub stage: usize,
+    /// Results of the learner against every opponent it met this round.
+    pub results: Vec<(Opponent, EvaluationResult)>,
+    /// Share of non-lost games against the perfect player after the round.
+    pub strength: f64,
+    pub snapshots: usize,
+}
+
+/// Trains `agent` against the opponent pool for the configured number of rounds.
+/// The learner's strength is measured after every round and picks the stage,
+/// and so the opponent mix, of the next round.
+pub fn run_curriculum<F>(
+    agent: &mut Agent,
+    config: &CurriculumConfig,
+    mut on_round: F,
+) -> Vec<RoundReport>
+where
+    F: FnMut(&RoundReport),
+{
+    let mut rng = StdRng::seed_from_u64(config.seed);
+    let mut random = RandomPlayer::new_seeded(config.seed.wrapping_add(1));
+    let mut heuristic = HeuristicPlayer::new_seeded(config.seed.wrapping_add(2));
+    let mut scripted: Vec<MinimaxPlayer> = Difficulty::ALL
+        .iter()
+        .enumerate()
+        .map(|(i, difficulty)| {
+            difficulty.player_seeded(config.seed.wrapping_add(3 + i as u64))
+        })
+        .collect();
+    let mut judge = PerfectPlayer::new_seeded(config.seed.wrapping_add(7));
+    let mut snapshots: Vec<Agent> = Vec::new();
+
+    let mut strength = measure_strength(agent, &mut judge, config.evaluation_games);
+    let mut reports = Vec::with_capacity(config.rounds);
+    for round in 0..config.rounds {
+        let stage = config.stage_for(strength);
+        let mix: Vec<(Opponent, f64)> = config.stages[stage]
+            .mix
+            .iter()
+            .copied()
+            .filter(|&(opponent, weight)| {
+                weight > 0.0 && (opponent != Opponent::Snapshot || !snapshots.is_empty())
+            })
+            .collect();
+        let mut results: Vec<(Opponent, EvaluationResult)> = mix
+            .iter()
+            .map(|&(opponent, _)| (opponent, EvaluationResult::default()))
+            .collect();
+        for game in 0..config.games_per_round {
+            let opponent = match mix.choose_weighted(&mut rng, |&(_, weight)| weight) {
+                Ok(&(opponent, _)) => opponent,
+                Err(_) => Opponent::Random,
+            };
+            let player: &mut dyn Player = match opponent {
+                Opponent::Random => &mut random,
+                Opponent::Heuristic => &mut heuristic,
+                Opponent::Scripted(difficulty) => {
+                    let index = Difficulty::ALL.iter().position(|&d| d == difficulty);
+                    &mut scripted[index.unwrap()]
+                }
+                Opponent::Snapshot => snapshots.choose_mut(&mut rng).unwrap(),
+            };
+            let agent_side = if game % 2 == 0 {
+                FieldState::X
+            } else {
+                FieldState::O
+            };
+            let winner = training::train_game(agent, player, config.rewards, agent_side);
+
+            let result = match results.iter_mut().find(|(o, _)| *o == opponent) {
+                Some((_, result)) => result,
+                None => {
+                    results.push((opponent, EvaluationResult::default()));
+                    &mut results.last_mut().unwrap().1
+                }
+            };
+            result.record_winner(winner, agent_side);
+        }
+
+        if config.snapshot_every > 0 && (round + 1) % config.snapshot_every == 0 {
+            snapshots.push(agent.clone());
+            if snapshots.len() > config.max_snapshots {
+                snapshots.remove(0);
+            }
+        }
+        strength = measure_strength(agent, &mut judge, config.evaluation_games);
+        let report = RoundReport {
+            round,
+            stage,
+            results,
+            strength,
+            snapshots: snapshots.len(),
+        };
+        on_round(&report);
+        reports.push(report);
+    }
+    reports
+}
+
+fn measure_strength(agent: &mut Agent, judge: &mut PerfectPlayer, games: usize) -> f64 {
+    let result = evaluation::evaluate_as_cross(agent, judge, games)
+        + evaluation::evaluate_as_circle(agent, judge, games);
+    result.non_loss_rate()
+}
+
+#[test]
+fn stage_for_test() {
+    let config = CurriculumConfig::default();
+    assert_eq!(config.stage_for(0.0), 0);
+    assert_eq!(config.stage_for(0.49), 0);
+    assert_eq!(config.stage_for(0.5), 1);
+    assert_eq!(config.stage_for(0.9), 2);
+    assert_eq!(config.stage_for(1.0), 3);
+}
@@ -0,0 +1,154 @@
+use crate::agent::AgentAction;
+use crate::board::{Board, FieldPosition, FieldState};
+use crate::memory::BoardTransformation;
+
+/// How a move was picked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecisionKind {
+    /// Only one field was left.
+    Forced,
+    /// A random move, taken with probability epsilon.
+    Exploration,
+    /// The move with the highest bias.
+    Exploitation,
+}
+
+impl std::fmt::Display for DecisionKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DecisionKind::Forced => write!(f, "forced"),
+            DecisionKind::Exploration => write!(f, "exploration"),
+            DecisionKind::Exploitation => write!(f, "exploitation"),
+        }
+    }
+}
+
+/// Report of how an [`Agent`](crate::agent::Agent) picked a move, returned by
+/// [`Agent::decide`](crate::agent::Agent::decide).
+///
+/// Its `Display` shows the report as a heatmap of the biases on the board.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Decision {
+    /// The board the move was picked on.
+    pub board: Board,
+    pub chosen: FieldPosition,
+    pub kind: DecisionKind,
+    /// The exploration rate the move was picked with.
+    pub epsilon: f64,
+    /// The version of the board in the agent's memory, with colors swapped for O
+    /// in a shared table. Boards which are not memorized appear as they are.
+    pub saved_board: Board,
+    /// Transformation leading from `board` to `saved_board`.
+    pub transformation: BoardTransformation,
+    /// Every move the agent knows on the board with its bias and visit count,
+    /// positioned on `board` rather than `saved_board`.
+    pub candidates: Vec<AgentAction>,
+}
+
+/// Shades from the lowest to the highest bias of a board.
+const SHADES: [char; 4] = ['░', '▒', '▓', '█'];
+const CELL_WIDTH: usize = 21;
+
+impl Decision {
+    pub fn candidate(&self, pos: FieldPosition) -> Option<&AgentAction> {
+        self.candidates
+            .iter()
+            .find(|action| action.field_pos == pos)
+    }
+
+    /// Renders the board with the shade, bias and visit count of every candidate
+    /// in its field, the chosen move in brackets.
+    pub fn heatmap(&self) -> String {
+        let min = self.candidates.iter().map(|action| action.bias).min();
+        let max = self.candidates.iter().map(|action| action.bias).max();
+        let shade = |bias: i32| match (min, max) {
+            (Some(min), Some(max)) if max > min => {
+                let level =
+                    (bias - min) as usize * (SHADES.len() - 1) / (max - min) as usize;
+                SHADES[level]
+            }
+            _ => SHADES[0],
+        };
+        let rows: Vec<String> = (0..3)
+            .map(|y| {
+                let cells: Vec<String> = (0..3)
+                    .map(|x| {
+                        let pos = FieldPosition::new(x, y);
+                        let text = match (self.board.0[y][x], self.candidate(pos)) {
+                            (FieldState::Empty, Some(action)) => {
+                                let shade = shade(action.bias);
+                                format!(
+                                    "{}{} {:+} ({})",
+                                    shade, shade, action.bias, action.visits
+                                )
+                            }
+                            (FieldState::Empty, None) => String::new(),
+                            (field, _) => field.to_string(),
+                        };
+                        let text = if pos == self.chosen {
+                            format!("[{}]", text)
+                        } else {
+                            text
+                        };
+                        format!("{:^width$}", text, width = CELL_WIDTH)
+                    })
+                    .collect();
+                cells.join("|")
+            })
+            .collect();
+        rows.join(&format!("\n{}\n", "-".repeat(CELL_WIDTH * 3 + 2)))
+    }
+}
+
+impl std::fmt::Display for Decision {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "{} plays ({}, {}) by {}, epsilon {:.3}",
+            self.board.next_player(),
+            self.chosen.x,
+            self.chosen.y,
+            self.kind,
+            self.epsilon
+        )?;
+        writeln!(
+            f,
+            "memorized as {} ({}), fields show bias and (visits)",
+            self.saved_board.to_code(),
+            self.transformation
+        )?;
+        writeln!(f, "{}", self.heatmap())
+    }
+}
+
+#[test]
+fn decision_test() {
+    use crate::agent::{Agent, AgentConfig};
+
+    let config = AgentConfig {
+        epsilon: 0.0,
+        ..Default::default()
+    };
+    let mut agent = Agent::new_seeded(config, 0);
+    let mut board = Board::EMPTY;
+    board.play_move_at(FieldPosition::new(1, 1));
+    board.play_move_at(FieldPosition::new(0, 0));
+    let first = agent.decide(&board).unwrap();
+    assert_eq!(first.kind, DecisionKind::Exploitation);
+    assert_eq!(first.transformation, BoardTransformation::None);
+    agent.give_feedback(6);
+
+    // A mirrored board is looked up in the memory of the first one
+    let mirrored = board.get_flipped_horizontally();
+    let decision = agent.decide(&mirrored).unwrap();
+    assert_eq!(decision.saved_board, board);
+    assert_ne!(decision.transformation, BoardTransformation::None);
+    let best = decision.candidate(decision.chosen).unwrap();
+    assert_eq!((best.bias, best.visits), (6, 1));
+    assert_eq!(
+        decision.transformation.pos_to_saved(decision.chosen),
+        first.chosen
+    );
+    assert_eq!(decision.candidates.len(), 7);
+    assert!(decision.heatmap().contains("[██ +6 (1)]"));
+}
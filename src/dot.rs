@@ -0,0 +1,210 @@
+use crate::agent::{Agent, AgentAction};
+use crate::board::{Board, FieldState};
+use crate::memory::BoardMemory;
+use crate::minimax::Minimax;
+use hashbrown::HashSet;
+use std::collections::VecDeque;
+use std::io::{self, Write};
+
+/// Limits of the exported part of the game tree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DotOptions {
+    /// Moves from the empty board after which the tree is cut off.
+    pub max_depth: usize,
+    /// Moves played in fewer finished games are left out, except for moves on
+    /// the last empty field which the agent does not count.
+    pub min_visits: u32,
+}
+
+impl Default for DotOptions {
+    fn default() -> Self {
+        Self {
+            max_depth: 9,
+            min_visits: 0,
+        }
+    }
+}
+
+const CROSS_WON_COLOR: &str = "#f4a6a6";
+const CIRCLE_WON_COLOR: &str = "#a6c8f4";
+const DRAW_COLOR: &str = "#dddddd";
+const GREEDY_COLOR: &str = "#1f6feb";
+const MISTAKE_COLOR: &str = "#d73a49";
+
+/// Writes the game tree memorized by `agent`, starting from the empty board, as
+/// a Graphviz DOT graph.
+///
+/// Nodes are positions, with all symmetric versions of a position merged into
+/// one node, and edges are the moves the agent knows, labeled with field number,
+/// bias and visit count. The moves the agent plays greedily from the empty board
+/// are drawn thick and blue, moves which are worse than the minimax result are
+/// dashed, red unless greedy, and finished games are filled with the color of
+/// their result.
+pub fn write_policy_tree<W: Write + ?Sized>(
+    agent: &Agent,
+    options: &DotOptions,
+    writer: &mut W,
+) -> io::Result<()> {
+    // The first version of every position seen names its node
+    let mut nodes = BoardMemory::new();
+    let mut canonical = |board: Board| match nodes.find(&board) {
+        Some((saved_board, _)) => saved_board,
+        None => {
+            nodes.insert(board.clone(), ());
+            board
+        }
+    };
+    canonical(Board::EMPTY);
+
+    // Follow the greedy moves first so the path is drawn in node orientation
+    let mut greedy_edges = HashSet::new();
+    let mut greedy_nodes = HashSet::new();
+    let mut board = Board::EMPTY;
+    greedy_nodes.insert(board.clone());
+    while let Some(action) = known_moves(agent, &board).into_iter().max() {
+        let mut next = board.clone();
+        next.play_move_at(action.field_pos);
+        greedy_edges.insert((board.clone(), action.field_pos));
+        let next = canonical(next);
+        greedy_nodes.insert(next.clone());
+        board = next;
+    }
+
+    writeln!(writer, "digraph policy {{")?;
+    writeln!(writer, "    node [shape=box, fontname=\"monospace\"];")?;
+    let mut minimax = Minimax::new();
+    let mut queue = VecDeque::from([Board::EMPTY]);
+    let mut written = HashSet::new();
+    while let Some(board) = queue.pop_front() {
+        if !written.insert(board.clone()) {
+            continue;
+        }
+        write_node(writer, &board, greedy_nodes.contains(&board))?;
+        let empty_field_count = board.field_state_count(FieldState::Empty);
+        if board.is_finished() || 9 - empty_field_count >= options.max_depth {
+            continue;
+        }
+        let scores = minimax.move_scores(&board);
+        let best = scores.iter().map(|&(_, score)| score).max();
+        for action in known_moves(agent, &board) {
+            if action.visits < options.min_visits && empty_field_count > 1 {
+                continue;
+            }
+            let mut next = board.clone();
+            next.play_move_at(action.field_pos);
+            let next = canonical(next);
+            let pos = action.field_pos;
+            let mut attributes = vec![format!(
+                "label=\"{}: {:+} ({})\"",
+                pos.y * 3 + pos.x + 1,
+                action.bias,
+                action.visits
+            )];
+            let greedy = greedy_edges.contains(&(board.clone(), pos));
+            if greedy {
+                attributes.push(format!("color=\"{}\", penwidth=3", GREEDY_COLOR));
+            }
+            let score = scores.iter().find(|&&(other, _)| other == pos);
+            if score.map(|&(_, score)| score) < best {
+                attributes.push("style=dashed".to_string());
+                if !greedy {
+                    attributes.push(format!("color=\"{}\"", MISTAKE_COLOR));
+                }
+            }
+            writeln!(
+                writer,
+                "    \"{}\" -> \"{}\" [{}];",
+                board.to_code(),
+                next.to_code(),
+                attributes.join(", ")
+            )?;
+            queue.push_back(next);
+        }
+    }
+    writeln!(writer, "}}")
+}
+
+/// Returns the moves the agent knows on `board`, or the last empty field which
+/// the agent plays without memorizing the board.
+fn known_moves(agent: &Agent, board: &Board) -> Vec<AgentAction> {
+    if board.is_finished() {
+        return Vec::new();
+    }
+    let empty_fields = board.get_empty_fields_pos();
+    if let [pos] = empty_fields[..] {
+        return vec![AgentAction::new(pos)];
+    }
+    agent.get_actions_from_board(board).unwrap_or_default()
+}
+
+fn write_node<W: Write + ?Sized>(
+    writer: &mut W,
+    board: &Board,
+    greedy: bool,
+) -> io::Result<()> {
+    let rows: Vec<String> = board
+        .to_code()
+        .as_bytes()
+        .chunks(3)
+        .map(|row| String::from_utf8_lossy(row).into_owned())
+        .collect();
+    let mut attributes = vec![format!("label=\"{}\"", rows.join("\\n"))];
+    if board.is_finished() {
+        let color = match board.find_winner() {
+            FieldState::X => CROSS_WON_COLOR,
+            FieldState::O => CIRCLE_WON_COLOR,
+            FieldState::Empty => DRAW_COLOR,
+        };
+        attributes.push(format!("style=filled, fillcolor=\"{}\"", color));
+    }
+    if greedy {
+        attributes.push(format!("color=\"{}\", penwidth=3", GREEDY_COLOR));
+    }
+    writeln!(
+        writer,
+        "    \"{}\" [{}];",
+        board.to_code(),
+        attributes.join(", ")
+    )
+}
+
+#[test]
+fn policy_tree_test() {
+    // Opens in the center or a corner and answers the center with a corner or,
+    // losing by force, an edge
+    let agent = Agent::read_from(
+        "agent 0 0.999 0.1\n\
+         ......... 1,1:6:2 0,0:-3:1\n\
+         ....X.... 0,0:1:1 1,0:-3:1\n"
+            .as_bytes(),
+    )
+    .unwrap();
+    let edge = |dot: &str, from: &str, to: &str| {
+        let start = format!("    \"{}\" -> \"{}\" [", from, to);
+        dot.lines()
+            .find(|line| line.starts_with(&start))
+            .map(str::to_string)
+    };
+    let mut dot = Vec::new();
+    write_policy_tree(&agent, &DotOptions::default(), &mut dot).unwrap();
+    let dot = String::from_utf8(dot).unwrap();
+    assert!(dot.starts_with("digraph policy {"));
+    assert_eq!(dot.matches(" -> ").count(), 4);
+    let center = edge(&dot, ".........", "....X....").unwrap();
+    assert!(center.contains("5: +6 (2)") && center.contains("penwidth=3"));
+    assert!(!center.contains("dashed"));
+    let corner = edge(&dot, ".........", "X........").unwrap();
+    assert!(!corner.contains("penwidth") && !corner.contains("dashed"));
+    let reply = edge(&dot, "....X....", "O...X....").unwrap();
+    assert!(reply.contains("penwidth=3") && !reply.contains("dashed"));
+    let mistake = edge(&dot, "....X....", ".O..X....").unwrap();
+    assert!(mistake.contains("style=dashed") && mistake.contains(MISTAKE_COLOR));
+
+    let options = DotOptions {
+        min_visits: 2,
+        ..Default::default()
+    };
+    let mut dot = Vec::new();
+    write_policy_tree(&agent, &options, &mut dot).unwrap();
+    assert_eq!(String::from_utf8(dot).unwrap().matches(" -> ").count(), 1);
+}
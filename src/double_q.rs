@@ -0,0 +1,191 @@
+use crate::agent::AgentConfig;
+use crate::board::{Board, FieldPosition};
+use crate::memory::BoardMemory;
+use crate::player::{Learner, Player};
+use crate::td::{self, ActionValue};
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+
+/// Q-learning agent with two action value tables (Double Q-learning).
+///
+/// Plain Q-learning updates towards the maximum of its own noisy estimates and
+/// so overestimates actions early in training. Here every update picks one of
+/// the tables at random, selects the best next action with it and evaluates that
+/// action with the other table. Moves are chosen on the sum of both tables.
+#[derive(Clone)]
+pub struct DoubleQAgent {
+    first: BoardMemory<Vec<ActionValue>>,
+    /// Holds exactly the saved boards of `first`.
+    second: BoardMemory<Vec<ActionValue>>,
+    epsilon: f64,
+    epsilon_decay: f64,
+    min_epsilon: f64,
+    learning_rate: f64,
+    rng: StdRng,
+    /// Saved board and saved position of the last move waiting for its update.
+    pending: Option<(Board, FieldPosition)>,
+}
+
+impl DoubleQAgent {
+    pub fn new(config: AgentConfig, learning_rate: f64) -> Self {
+        Self::with_rng(config, learning_rate, StdRng::from_entropy())
+    }
+
+    pub fn new_seeded(config: AgentConfig, learning_rate: f64, seed: u64) -> Self {
+        Self::with_rng(config, learning_rate, StdRng::seed_from_u64(seed))
+    }
+
+    fn with_rng(config: AgentConfig, learning_rate: f64, rng: StdRng) -> Self {
+        Self {
+            first: BoardMemory::new(),
+            second: BoardMemory::new(),
+            epsilon: config.epsilon,
+            epsilon_decay: config.epsilon_decay,
+            min_epsilon: config.min_epsilon,
+            learning_rate,
+            rng,
+            pending: None,
+        }
+    }
+
+    /// Returns the known action values of a board in its original orientation,
+    /// averaged over both tables.
+    pub fn get_action_values(&self, board: &Board) -> Option<Vec<ActionValue>> {
+        let (saved_board, transformation) = self.first.find(board)?;
+        let combined = self.combined_values(&saved_board);
+        Some(
+            combined
+                .into_iter()
+                .map(|action| ActionValue {
+                    field_pos: transformation.pos_to_original(action.field_pos),
+                    value: action.value / 2.0,
+                })
+                .collect(),
+        )
+    }
+
+    pub fn memorized_boards_count(&self) -> usize {
+        self.first.len()
+    }
+
+    pub fn epsilon(&self) -> f64 {
+        self.epsilon
+    }
+
+    /// Sums the values of both tables for a saved board.
+    fn combined_values(&self, saved_board: &Board) -> Vec<ActionValue> {
+        let first = self.first.get(saved_board).unwrap();
+        let second = self.second.get(saved_board).unwrap();
+        first
+            .iter()
+            .zip(second)
+            .map(|(a, b)| ActionValue {
+                field_pos: a.field_pos,
+                value: a.value + b.value,
+            })
+            .collect()
+    }
+
+    /// Updates the pending move in one of the tables, towards the final reward or
+    /// towards the value of the saved board the agent moves on next.
+    fn update_pending(&mut self, next: Result<&Board, i32>) {
+        let (board, pos) = match self.pending.take() {
+            Some(pending) => pending,
+            None => return,
+        };
+        let (update, evaluate) = if self.rng.gen_bool(0.5) {
+            (&mut self.first, &self.second)
+        } else {
+            (&mut self.second, &self.first)
+        };
+        let target = match next {
+            Ok(next) => {
+                double_target(update.get(next).unwrap(), evaluate.get(next).unwrap())
+            }
+            Err(reward) => reward as f64,
+        };
+        let action = update
+            .get_mut(&board)
+            .unwrap()
+            .iter_mut()
+            .find(|action| action.field_pos == pos)
+            .unwrap();
+        action.value += self.learning_rate * (target - action.value);
+    }
+}
+
+/// Value under `evaluate` of the best action under `select`.
+fn double_target(select: &[ActionValue], evaluate: &[ActionValue]) -> f64 {
+    let best = select
+        .iter()
+        .zip(evaluate)
+        .max_by(|(a, _), (b, _)| a.value.total_cmp(&b.value));
+    best.map_or(0.0, |(_, action)| action.value)
+}
+
+impl Player for DoubleQAgent {
+    fn choose_move(&mut self, board: &Board) -> Option<FieldPosition> {
+        match self.get_action_values(board) {
+            Some(actions) => td::greedy_action(&actions, &mut self.rng),
+            None => board.get_empty_fields_pos().choose(&mut self.rng).copied(),
+        }
+    }
+}
+
+impl Learner for DoubleQAgent {
+    fn play_training_move(&mut self, board: &Board) -> Option<FieldPosition> {
+        if board.is_finished() {
+            return None;
+        }
+        let blank = || -> Vec<ActionValue> {
+            board
+                .get_empty_fields_pos()
+                .into_iter()
+                .map(|field_pos| ActionValue {
+                    field_pos,
+                    value: 0.0,
+                })
+                .collect()
+        };
+        let (saved_board, transformation) = self.first.find_or_insert_with(board, blank);
+        if self.second.get(&saved_board).is_none() {
+            self.second.insert(saved_board.clone(), blank());
+        }
+        let actions = self.combined_values(&saved_board);
+        let chosen = if self.rng.gen_bool(self.epsilon) {
+            actions.choose(&mut self.rng).map(|action| action.field_pos)
+        } else {
+            td::greedy_action(&actions, &mut self.rng)
+        }?;
+
+        self.update_pending(Ok(&saved_board));
+        self.pending = Some((saved_board, chosen));
+        Some(transformation.pos_to_original(chosen))
+    }
+
+    fn give_feedback(&mut self, reward: i32) {
+        if reward > 0 {
+            self.epsilon = (self.epsilon * self.epsilon_decay).max(self.min_epsilon);
+        }
+        self.update_pending(Err(reward));
+    }
+}
+
+#[test]
+fn double_target_test() {
+    let values = |a: f64, b: f64| {
+        [
+            ActionValue {
+                field_pos: FieldPosition::new(0, 0),
+                value: a,
+            },
+            ActionValue {
+                field_pos: FieldPosition::new(1, 0),
+                value: b,
+            },
+        ]
+    };
+    // The selecting table prefers the first action, so the evaluating table's
+    // value of it is used even though it rates the second one higher
+    assert_eq!(double_target(&values(5.0, 0.0), &values(-1.0, 3.0)), -1.0);
+    assert_eq!(double_target(&values(0.0, 5.0), &values(-1.0, 3.0)), 3.0);
+}
@@ -0,0 +1,109 @@
+use crate::board::{Board, FieldPosition};
+use crate::replay::Transition;
+use hashbrown::HashMap;
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+/// Learned model of the environment for Dyna-Q planning.
+///
+/// For every saved board and action the learner played it counts what happened
+/// next: the board the opponent's response left it to move on, or the reward if
+/// the game ended. Sampling the model replays those outcomes in the observed
+/// proportions, so it also models a randomly responding opponent.
+#[derive(Debug, Clone, Default)]
+pub struct DynaModel {
+    entries: Vec<ModelEntry>,
+    index: HashMap<(Board, FieldPosition), usize>,
+}
+
+#[derive(Debug, Clone)]
+struct ModelEntry {
+    board: Board,
+    action: FieldPosition,
+    /// Observed outcomes as reward, next saved board and count.
+    outcomes: Vec<(f64, Option<Board>, u32)>,
+}
+
+impl DynaModel {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of board and action pairs with a known outcome.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn record(&mut self, transition: &Transition) {
+        let key = (transition.board.clone(), transition.action);
+        let entries = &mut self.entries;
+        let index = *self.index.entry(key).or_insert_with(|| {
+            entries.push(ModelEntry {
+                board: transition.board.clone(),
+                action: transition.action,
+                outcomes: Vec::new(),
+            });
+            entries.len() - 1
+        });
+        let outcomes = &mut self.entries[index].outcomes;
+        match outcomes.iter_mut().find(|(reward, next, _)| {
+            *reward == transition.reward && *next == transition.next_board
+        }) {
+            Some((_, _, count)) => *count += 1,
+            None => outcomes.push((transition.reward, transition.next_board.clone(), 1)),
+        }
+    }
+
+    /// Picks a uniformly random known board and action and one of its outcomes
+    /// weighted by how often it was observed.
+    pub fn sample<R: Rng>(&self, rng: &mut R) -> Option<Transition> {
+        let entry = self.entries.choose(rng)?;
+        let (reward, next_board, _) = entry
+            .outcomes
+            .choose_weighted(rng, |&(_, _, count)| count)
+            .ok()?;
+        Some(Transition {
+            board: entry.board.clone(),
+            action: entry.action,
+            reward: *reward,
+            next_board: next_board.clone(),
+        })
+    }
+}
+
+#[test]
+fn dyna_model_test() {
+    use rand::{rngs::StdRng, SeedableRng};
+    let mut next = Board::EMPTY;
+    next.play_move_at(FieldPosition::new(1, 1));
+    next.play_move_at(FieldPosition::new(0, 0));
+    let won = Transition {
+        board: Board::EMPTY,
+        action: FieldPosition::new(1, 1),
+        reward: 6.0,
+        next_board: None,
+    };
+    let continued = Transition {
+        next_board: Some(next),
+        reward: 0.0,
+        ..won.clone()
+    };
+    let mut model = DynaModel::new();
+    for _ in 0..3 {
+        model.record(&continued);
+    }
+    model.record(&won);
+    assert_eq!(model.len(), 1);
+
+    let mut rng = StdRng::seed_from_u64(0);
+    let samples: Vec<Transition> =
+        (0..400).map(|_| model.sample(&mut rng).unwrap()).collect();
+    let terminal = samples.iter().filter(|t| t.is_terminal()).count();
+    // One in four observed outcomes ended the game
+    assert!((60..140).contains(&terminal));
+    assert!(samples.contains(&won) && samples.contains(&continued));
+}
@@ -0,0 +1,293 @@
+use crate::agent::Rewards;
+use crate::board::{Board, FieldPosition, FieldState};
+use crate::saved::SavedAgent;
+use std::io::{self, BufRead, Write};
+
+const COMMANDS: &str =
+    "name version list_commands isready newgame position play go setoption result show \
+     save quit";
+
+/// Line-based protocol driving an agent from another program, in the spirit of
+/// UCI and GTP.
+///
+/// The controller sends one command per line and the engine answers every
+/// command with exactly one line, `= <answer>` on success or `? <error>` on
+/// failure. Fields are numbered 1 to 9 row by row.
+///
+/// - `newgame` clears the board and forgets the moves of an unfinished game.
+/// - `position startpos|<board> [moves <field> ...]` sets up a board, given as
+///   nine `X`, `O` and `.` characters, and plays the listed moves on it. Moves
+///   already played by the engine are kept for the result, so a controller may
+///   send the whole game before every `go`.
+/// - `play <field>` plays a move for the side to move.
+/// - `go` answers `= bestmove <field>` and plays the move.
+/// - `setoption epsilon <rate>` sets the exploration rate, `setoption seed <n>`
+///   restarts the random number generator.
+/// - `result win|loss|draw` rewards the engine's moves of the game, from its own
+///   point of view, so the agent learns from the games it is told the result of.
+/// - `show` answers the board and `X`, `O`, `X won`, `O won` or `draw`.
+/// - `save <file>` saves the agent with everything it has learned.
+/// - `name`, `version`, `list_commands`, `isready` and `quit` answer `= <name>`,
+///   `= <version>`, `= <commands>`, `= readyok` and `= bye`.
+pub struct Engine {
+    agent: SavedAgent,
+    board: Board,
+    rewards: Rewards,
+    quit: bool,
+}
+
+impl Engine {
+    /// Creates an engine playing the agent's best moves, with exploration off
+    /// until `setoption epsilon` turns it on.
+    pub fn new(mut agent: SavedAgent) -> Self {
+        agent.set_epsilon(0.0);
+        Self {
+            agent,
+            board: Board::EMPTY,
+            rewards: Rewards::default(),
+            quit: false,
+        }
+    }
+
+    /// Rewards given for the results of the games, [`Rewards::default`] if not set.
+    pub fn with_rewards(mut self, rewards: Rewards) -> Self {
+        self.rewards = rewards;
+        self
+    }
+
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    /// Whether the controller has sent `quit`.
+    pub fn has_quit(&self) -> bool {
+        self.quit
+    }
+
+    /// Answers one command line, without the line break. Returns `None` for
+    /// blank lines.
+    pub fn handle(&mut self, line: &str) -> Option<String> {
+        let mut words = line.split_whitespace();
+        let command = words.next()?;
+        let args: Vec<&str> = words.collect();
+        Some(match self.execute(command, &args) {
+            Ok(answer) => format!("= {}", answer),
+            Err(err) => format!("? {}", err),
+        })
+    }
+
+    fn execute(&mut self, command: &str, args: &[&str]) -> Result<String, String> {
+        match (command, args) {
+            ("name", []) => Ok(env!("CARGO_PKG_NAME").to_string()),
+            ("version", []) => Ok(env!("CARGO_PKG_VERSION").to_string()),
+            ("list_commands" | "help", []) => Ok(COMMANDS.to_string()),
+            ("isready", []) => Ok("readyok".to_string()),
+            ("newgame", []) => {
+                self.agent.discard_game();
+                self.board = Board::EMPTY;
+                Ok("ok".to_string())
+            }
+            ("position", [start, moves @ ..]) => self.set_position(start, moves),
+            ("play", [field]) => {
+                let mut board = self.board.clone();
+                play_field(&mut board, field)?;
+                self.board = board;
+                Ok("ok".to_string())
+            }
+            ("go", []) => {
+                if self.board.is_finished() {
+                    return Err("the game is over".to_string());
+                }
+                let pos = self
+                    .agent
+                    .learner()
+                    .play_training_move(&self.board)
+                    .ok_or("no move")?;
+                self.board.play_move_at(pos);
+                Ok(format!("bestmove {}", pos.y * 3 + pos.x + 1))
+            }
+            ("setoption", ["epsilon", value]) => match value.parse::<f64>() {
+                Ok(epsilon) if (0.0..=1.0).contains(&epsilon) => {
+                    self.agent.set_epsilon(epsilon);
+                    Ok("ok".to_string())
+                }
+                _ => Err(format!("invalid epsilon `{}`", value)),
+            },
+            ("setoption", ["seed", value]) => {
+                let seed = value
+                    .parse()
+                    .map_err(|_| format!("invalid seed `{}`", value))?;
+                self.agent.reseed(seed);
+                Ok("ok".to_string())
+            }
+            ("setoption", [name, ..]) => Err(format!("unknown option `{}`", name)),
+            ("result", [result]) => {
+                let reward = match *result {
+                    "win" => self.rewards.win,
+                    "loss" => self.rewards.loss,
+                    "draw" => self.rewards.draw,
+                    _ => return Err(format!("invalid result `{}`", result)),
+                };
+                // Decaying would lift a rate of 0 to the agent's lowest rate
+                let epsilon = self.agent.epsilon();
+                self.agent.learner().give_feedback(reward);
+                if epsilon == 0.0 {
+                    self.agent.set_epsilon(0.0);
+                }
+                Ok("ok".to_string())
+            }
+            ("show", []) => {
+                let state = match self.board.find_winner() {
+                    FieldState::X => "X won",
+                    FieldState::O => "O won",
+                    _ if self.board.is_finished() => "draw",
+                    _ if self.board.next_player() == FieldState::O => "O",
+                    _ => "X",
+                };
+                Ok(format!("{} {}", self.board.to_code(), state))
+            }
+            ("save", [path]) => self
+                .agent
+                .save(path)
+                .map(|_| "ok".to_string())
+                .map_err(|err| format!("failed to save `{}`: {}", path, err)),
+            ("quit", []) => {
+                self.quit = true;
+                Ok("bye".to_string())
+            }
+            _ if COMMANDS.split(' ').any(|known| known == command) => {
+                Err(format!("wrong arguments for `{}`", command))
+            }
+            _ => Err(format!("unknown command `{}`", command)),
+        }
+    }
+
+    fn set_position(&mut self, start: &str, moves: &[&str]) -> Result<String, String> {
+        let mut board = match start {
+            "startpos" => Board::EMPTY,
+            code => Board::from_code(code)
+                .ok_or_else(|| format!("invalid board `{}`", code))?,
+        };
+        let crosses = board.field_state_count(FieldState::X);
+        let circles = board.field_state_count(FieldState::O);
+        if crosses != circles && crosses != circles + 1 {
+            return Err(format!("board `{}` cannot occur, X always starts", start));
+        }
+        match moves {
+            [] => (),
+            ["moves", fields @ ..] => {
+                for field in fields {
+                    play_field(&mut board, field)?;
+                }
+            }
+            _ => return Err(format!("expected `moves` instead of `{}`", moves[0])),
+        }
+        self.board = board;
+        Ok("ok".to_string())
+    }
+}
+
+/// Plays the field numbered `field` on `board` if it is a legal move.
+fn play_field(board: &mut Board, field: &str) -> Result<(), String> {
+    let pos = match field.parse::<usize>() {
+        Ok(n @ 1..=9) => FieldPosition::new((n - 1) % 3, (n - 1) / 3),
+        _ => return Err(format!("invalid field `{}`", field)),
+    };
+    if board.is_finished() {
+        return Err("the game is over".to_string());
+    }
+    if board.0[pos.y][pos.x] != FieldState::Empty {
+        return Err(format!("field {} is taken", field));
+    }
+    board.play_move_at(pos);
+    Ok(())
+}
+
+/// Answers the commands read from `input` on `output` until `quit` or the end of
+/// the input.
+pub fn run<R: BufRead, W: Write>(
+    engine: &mut Engine,
+    input: R,
+    output: &mut W,
+) -> io::Result<()> {
+    for line in input.lines() {
+        if let Some(answer) = engine.handle(&line?) {
+            writeln!(output, "{}", answer)?;
+            output.flush()?;
+        }
+        if engine.has_quit() {
+            break;
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn engine_test() {
+    use crate::agent::{Agent, AgentConfig};
+
+    let agent = Agent::new_seeded(AgentConfig::default(), 0);
+    let mut engine = Engine::new(SavedAgent::Table(agent));
+    let input = "isready\n\
+                 \n\
+                 position startpos moves 5 1\n\
+                 go\n\
+                 position XXO.X.O.. moves 4 9\n\
+                 show\n\
+                 result win\n\
+                 play 1\n\
+                 newgame\n\
+                 play 5\n\
+                 play 5\n\
+                 setoption temperature 1\n\
+                 dance\n\
+                 quit\n\
+                 isready\n";
+    let mut output = Vec::new();
+    run(&mut engine, input.as_bytes(), &mut output).unwrap();
+    let output = String::from_utf8(output).unwrap();
+    let lines: Vec<&str> = output.lines().collect();
+    assert_eq!(lines.len(), 13);
+    assert_eq!(lines[0], "= readyok");
+    assert!(lines[2].starts_with("= bestmove "));
+    assert_ne!(lines[2], "= bestmove 5");
+    assert_ne!(lines[2], "= bestmove 1");
+    assert_eq!(lines[4], "= XXOOX.O.X X won");
+    assert_eq!(lines[6], "? the game is over");
+    assert_eq!(lines[9], "? field 5 is taken");
+    assert_eq!(lines[10], "? unknown option `temperature`");
+    assert_eq!(lines[11], "? unknown command `dance`");
+    assert_eq!(lines[12], "= bye");
+
+    // The win rewarded the engine's move on the board after 5 and 1
+    let mut board = Board::EMPTY;
+    board.play_move_at(FieldPosition::new(1, 1));
+    board.play_move_at(FieldPosition::new(0, 0));
+    let SavedAgent::Table(agent) = &engine.agent else {
+        unreachable!()
+    };
+    let actions = agent.get_actions_from_board(&board).unwrap();
+    let rewarded = actions.iter().max().unwrap();
+    assert_eq!(
+        (rewarded.bias, rewarded.visits),
+        (Rewards::default().win, 1)
+    );
+}
+
+#[test]
+fn engine_exploration_test() {
+    let agent = SavedAgent::read_from(
+        "agent 0.6 0.999 0.1\n\
+         ......... 1,1:6:2 0,0:-3:1\n"
+            .as_bytes(),
+    )
+    .unwrap();
+    let mut engine = Engine::new(agent);
+    // Wins decay the exploration rate, which has to stay off
+    for _ in 0..50 {
+        assert_eq!(engine.handle("newgame").unwrap(), "= ok");
+        assert_eq!(engine.handle("go").unwrap(), "= bestmove 5");
+        assert_eq!(engine.handle("result win").unwrap(), "= ok");
+    }
+    assert_eq!(engine.agent.epsilon(), 0.0);
+}
@@ -0,0 +1,254 @@
+use crate::agent::Rewards;
+use crate::board::{Board, FieldPosition, FieldState};
+use crate::game::GameEvent;
+use crate::player::Player;
+
+/// Number of actions, one per field, numbered `y * 3 + x` from 0 to 8.
+pub const ACTIONS: usize = 9;
+
+/// What the learner sees of the game before it acts.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Observation {
+    pub board: Board,
+    /// The side the learner plays.
+    pub side: FieldState,
+}
+
+impl Observation {
+    /// One-hot encoding of every field as empty, own or opponent mark, three
+    /// inputs per field in action order.
+    pub fn features(&self) -> Vec<f64> {
+        let mut features = vec![0.0; ACTIONS * 3];
+        for (i, field) in self.board.0.iter().flatten().enumerate() {
+            let offset = match *field {
+                FieldState::Empty => 0,
+                field if field == self.side => 1,
+                _ => 2,
+            };
+            features[i * 3 + offset] = 1.0;
+        }
+        features
+    }
+
+    /// Which actions are legal, none once the game is over.
+    pub fn action_mask(&self) -> [bool; ACTIONS] {
+        let mut mask = [false; ACTIONS];
+        if !self.board.is_finished() {
+            for pos in self.board.get_empty_fields_pos() {
+                mask[action_of(pos)] = true;
+            }
+        }
+        mask
+    }
+
+    pub fn legal_actions(&self) -> Vec<usize> {
+        let mask = self.action_mask();
+        (0..ACTIONS).filter(|&action| mask[action]).collect()
+    }
+}
+
+/// Details of a step beyond the reward.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StepInfo {
+    /// State of the game after the step.
+    pub event: GameEvent,
+    /// The reply of the built-in opponent, if the game went on after the action.
+    pub opponent_action: Option<usize>,
+}
+
+/// Result of [`Env::step`], the `(observation, reward, done, info)` of Gym.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Step {
+    pub observation: Observation,
+    pub reward: f64,
+    pub done: bool,
+    pub info: StepInfo,
+}
+
+/// Which side the learner takes in the next game.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SideChoice {
+    Cross,
+    Circle,
+    /// Switches sides with every reset, starting as X.
+    Alternate,
+}
+
+/// Gym-style environment: the learner plays one side of a game against a
+/// built-in opponent which replies within the same step.
+///
+/// [`Env::reset`] starts a game, letting the opponent open if the learner plays
+/// O, and [`Env::step`] plays the learner's action followed by the opponent's
+/// reply. The reward is zero until the game ends and then the win, loss or draw
+/// reward from the learner's point of view.
+pub struct Env {
+    opponent: Box<dyn Player>,
+    side_choice: SideChoice,
+    rewards: Rewards,
+    board: Board,
+    side: FieldState,
+    games: usize,
+}
+
+impl Env {
+    /// Creates an environment where the learner plays X against `opponent`,
+    /// rewarded 1 for a win, -1 for a loss and 0 for a draw.
+    pub fn new(opponent: Box<dyn Player>) -> Self {
+        Self {
+            opponent,
+            side_choice: SideChoice::Cross,
+            rewards: Rewards {
+                win: 1,
+                loss: -1,
+                draw: 0,
+            },
+            board: Board::EMPTY,
+            side: FieldState::X,
+            games: 0,
+        }
+    }
+
+    pub fn with_side(mut self, side_choice: SideChoice) -> Self {
+        self.side_choice = side_choice;
+        self
+    }
+
+    pub fn with_rewards(mut self, rewards: Rewards) -> Self {
+        self.rewards = rewards;
+        self
+    }
+
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    /// The side the learner plays in the current game.
+    pub fn side(&self) -> FieldState {
+        self.side
+    }
+
+    fn observation(&self) -> Observation {
+        Observation {
+            board: self.board.clone(),
+            side: self.side,
+        }
+    }
+
+    /// Starts a new game and returns the first board the learner has to move on.
+    pub fn reset(&mut self) -> Observation {
+        self.side = match self.side_choice {
+            SideChoice::Cross => FieldState::X,
+            SideChoice::Circle => FieldState::O,
+            SideChoice::Alternate if self.games.is_multiple_of(2) => FieldState::X,
+            SideChoice::Alternate => FieldState::O,
+        };
+        self.games += 1;
+        self.board = Board::EMPTY;
+        if self.side == FieldState::O {
+            self.opponent_move();
+        }
+        self.observation()
+    }
+
+    /// Plays `action` for the learner and, unless that ends the game, the
+    /// opponent's reply. Fails without changing the game on an illegal action or
+    /// a finished game.
+    pub fn step(&mut self, action: usize) -> Result<Step, String> {
+        if GameEvent::of(&self.board).is_finished() {
+            return Err("the game is over, reset the environment".to_string());
+        }
+        let pos = match action {
+            action if action < ACTIONS => FieldPosition::new(action % 3, action / 3),
+            _ => return Err(format!("action {} is out of range", action)),
+        };
+        if self.board.0[pos.y][pos.x] != FieldState::Empty {
+            return Err(format!("field of action {} is taken", action));
+        }
+        self.board.play_move_at(pos);
+        let mut opponent_action = None;
+        if !GameEvent::of(&self.board).is_finished() {
+            opponent_action = Some(self.opponent_move());
+        }
+
+        let event = GameEvent::of(&self.board);
+        let reward = match self.board.find_winner() {
+            _ if !event.is_finished() => 0,
+            FieldState::Empty => self.rewards.draw,
+            winner if winner == self.side => self.rewards.win,
+            _ => self.rewards.loss,
+        };
+        Ok(Step {
+            observation: self.observation(),
+            reward: reward as f64,
+            done: event.is_finished(),
+            info: StepInfo {
+                event,
+                opponent_action,
+            },
+        })
+    }
+
+    fn opponent_move(&mut self) -> usize {
+        let pos = self
+            .opponent
+            .choose_move(&self.board)
+            .expect("the opponent has to move on an unfinished board");
+        assert_eq!(
+            self.board.0[pos.y][pos.x],
+            FieldState::Empty,
+            "the opponent played a taken field"
+        );
+        self.board.play_move_at(pos);
+        action_of(pos)
+    }
+}
+
+fn action_of(pos: FieldPosition) -> usize {
+    pos.y * 3 + pos.x
+}
+
+#[test]
+fn env_test() {
+    use crate::minimax::PerfectPlayer;
+
+    let opponent = Box::new(PerfectPlayer::new_seeded(0));
+    let mut env = Env::new(opponent).with_side(SideChoice::Alternate);
+    let observation = env.reset();
+    assert_eq!(observation.board, Board::EMPTY);
+    assert_eq!(observation.legal_actions().len(), 9);
+    assert!(env.step(9).is_err());
+
+    // The lowest legal action every time loses against a perfect player
+    let mut observation = observation;
+    let mut total_reward = 0.0;
+    loop {
+        let action = observation.legal_actions()[0];
+        let step = env.step(action).unwrap();
+        assert!(!step.observation.action_mask()[action]);
+        total_reward += step.reward;
+        observation = step.observation;
+        if step.done {
+            assert_eq!(step.info.event, GameEvent::CircleWon);
+            break;
+        }
+        assert!(step.info.opponent_action.is_some());
+    }
+    assert_eq!(total_reward, -1.0);
+    assert_eq!(observation.action_mask(), [false; ACTIONS]);
+    assert!(env
+        .step(observation.board.get_empty_fields_pos().len())
+        .is_err());
+
+    // As O the opponent opens and the features show its mark as the opponent's
+    let observation = env.reset();
+    assert_eq!(env.side(), FieldState::O);
+    assert_eq!(observation.board.field_state_count(FieldState::X), 1);
+    let features = observation.features();
+    assert_eq!(features.iter().sum::<f64>(), 9.0);
+    assert_eq!(
+        features.chunks(3).filter(|field| field[2] == 1.0).count(),
+        1
+    );
+    let taken = (0..ACTIONS).find(|&action| !observation.action_mask()[action]);
+    assert!(env.step(taken.unwrap()).is_err());
+}
@@ -0,0 +1,127 @@
+use crate::board::FieldState;
+use crate::game::{GameEvent, GameState};
+use crate::player::Player;
+
+/// Plays a single game and returns the final event:
+/// [`GameEvent::CrossWon`], [`GameEvent::CircleWon`] or [`GameEvent::Draw`].
+pub fn play_game(cross: &mut dyn Player, circle: &mut dyn Player) -> GameEvent {
+    let mut result = GameEvent::Draw;
+    GameState::new().run_new(|event, board_state| match event {
+        GameEvent::CrossWon | GameEvent::CircleWon | GameEvent::Draw => result = event,
+        GameEvent::CrossTurn => {
+            let position = cross.choose_move(board_state);
+            board_state.play_move_at(position.unwrap());
+        }
+        GameEvent::CircleTurn => {
+            let position = circle.choose_move(board_state);
+            board_state.play_move_at(position.unwrap());
+        }
+        GameEvent::InvalidBoard => unreachable!(),
+    });
+    result
+}
+
+/// Game results counted from the evaluated player's point of view.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct EvaluationResult {
+    pub wins: usize,
+    pub draws: usize,
+    pub losses: usize,
+}
+
+impl EvaluationResult {
+    pub fn games(&self) -> usize {
+        self.wins + self.draws + self.losses
+    }
+
+    /// Share of games which were not lost.
+    pub fn non_loss_rate(&self) -> f64 {
+        if self.games() == 0 {
+            return 0.0;
+        }
+        (self.wins + self.draws) as f64 / self.games() as f64
+    }
+
+    /// Points per game, counting a win as one and a draw as half a point.
+    pub fn score(&self) -> f64 {
+        if self.games() == 0 {
+            return 0.0;
+        }
+        (self.wins as f64 + 0.5 * self.draws as f64) / self.games() as f64
+    }
+
+    /// The same results as seen by the opponent.
+    pub fn opponent_view(&self) -> Self {
+        Self {
+            wins: self.losses,
+            draws: self.draws,
+            losses: self.wins,
+        }
+    }
+
+    /// Counts a finished game won by `winner` ([`FieldState::Empty`] for a draw)
+    /// for the player who played `side`.
+    pub fn record_winner(&mut self, winner: FieldState, side: FieldState) {
+        if winner == FieldState::Empty {
+            self.draws += 1;
+        } else if winner == side {
+            self.wins += 1;
+        } else {
+            self.losses += 1;
+        }
+    }
+
+    fn record(&mut self, event: GameEvent, played_cross: bool) {
+        match (event, played_cross) {
+            (GameEvent::CrossWon, true) | (GameEvent::CircleWon, false) => self.wins += 1,
+            (GameEvent::CrossWon, false) | (GameEvent::CircleWon, true) => {
+                self.losses += 1
+            }
+            _ => self.draws += 1,
+        }
+    }
+}
+
+impl std::ops::Add for EvaluationResult {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            wins: self.wins + other.wins,
+            draws: self.draws + other.draws,
+            losses: self.losses + other.losses,
+        }
+    }
+}
+
+impl std::fmt::Display for EvaluationResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{}/{}", self.wins, self.draws, self.losses)
+    }
+}
+
+/// Plays `games` games with `player` as X against `opponent`.
+pub fn evaluate_as_cross(
+    player: &mut dyn Player,
+    opponent: &mut dyn Player,
+    games: usize,
+) -> EvaluationResult {
+    let mut result = EvaluationResult::default();
+    for _ in 0..games {
+        result.record(play_game(player, opponent), true);
+    }
+    result
+}
+
+/// Plays `games` games with `player` as O against `opponent`.
+pub fn evaluate_as_circle(
+    player: &mut dyn Player,
+    opponent: &mut dyn Player,
+    games: usize,
+) -> EvaluationResult {
+    let mut result = EvaluationResult::default();
+    for _ in 0..games {
+        result.record(play_game(opponent, player), false);
+    }
+    result
+}
@@ -0,0 +1,454 @@
+use crate::board::{Board, FieldPosition, FieldState};
+use crate::saved::SavedAgent;
+use std::ffi::{c_char, c_double, c_int, CStr};
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+
+/// Version of the C ABI, raised on every incompatible change of the header.
+pub const TTT_ABI_VERSION: u32 = 1;
+
+/// Result of the functions of the C ABI declared in `include/tic_tac_toe.h`.
+///
+/// Boards and agents are opaque handles owned by the caller, who frees them with
+/// [`ttt_board_free`] and [`ttt_agent_free`]. Every function that can fail returns
+/// a status and writes its result through an out pointer; panics are caught and
+/// reported as [`TttStatus::Panic`] instead of unwinding into C.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TttStatus {
+    Ok = 0,
+    NullPointer = 1,
+    InvalidField = 2,
+    FieldTaken = 3,
+    GameOver = 4,
+    InvalidBoard = 5,
+    InvalidUtf8 = 6,
+    Io = 7,
+    Panic = 8,
+    NoMove = 9,
+}
+
+const STATUS_MESSAGES: [&CStr; 10] = [
+    c"ok",
+    c"null pointer",
+    c"field out of range",
+    c"field is taken",
+    c"the game is over",
+    c"invalid board",
+    c"invalid UTF-8",
+    c"failed to read or write the agent",
+    c"internal error",
+    c"the agent has no move",
+];
+
+/// Runs `f`, turning a panic into [`TttStatus::Panic`].
+fn guard<F: FnOnce() -> Result<(), TttStatus>>(f: F) -> TttStatus {
+    match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(())) => TttStatus::Ok,
+        Ok(Err(status)) => status,
+        Err(_) => TttStatus::Panic,
+    }
+}
+
+/// Dereferences a handle or argument passed in by C.
+///
+/// # Safety
+///
+/// `ptr` must be null or valid for the lifetime of the call.
+unsafe fn arg<'a, T>(ptr: *const T) -> Result<&'a T, TttStatus> {
+    ptr.as_ref().ok_or(TttStatus::NullPointer)
+}
+
+/// # Safety
+///
+/// `ptr` must be null or valid and unaliased for the lifetime of the call.
+unsafe fn arg_mut<'a, T>(ptr: *mut T) -> Result<&'a mut T, TttStatus> {
+    ptr.as_mut().ok_or(TttStatus::NullPointer)
+}
+
+/// # Safety
+///
+/// `ptr` must be null or a NUL-terminated string valid for the call.
+unsafe fn arg_str<'a>(ptr: *const c_char) -> Result<&'a str, TttStatus> {
+    if ptr.is_null() {
+        return Err(TttStatus::NullPointer);
+    }
+    CStr::from_ptr(ptr)
+        .to_str()
+        .map_err(|_| TttStatus::InvalidUtf8)
+}
+
+fn position(field: c_int) -> Result<FieldPosition, TttStatus> {
+    match usize::try_from(field) {
+        Ok(field) if field < 9 => Ok(FieldPosition::new(field % 3, field / 3)),
+        _ => Err(TttStatus::InvalidField),
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn ttt_abi_version() -> u32 {
+    TTT_ABI_VERSION
+}
+
+/// Returns a static, NUL-terminated description of `status`.
+#[no_mangle]
+pub extern "C" fn ttt_status_message(status: c_int) -> *const c_char {
+    usize::try_from(status)
+        .ok()
+        .and_then(|index| STATUS_MESSAGES.get(index))
+        .map_or(c"unknown status".as_ptr(), |message| message.as_ptr())
+}
+
+/// Returns a new empty board, to be freed with [`ttt_board_free`].
+#[no_mangle]
+pub extern "C" fn ttt_board_new() -> *mut Board {
+    Box::into_raw(Box::new(Board::EMPTY))
+}
+
+/// Decodes nine `X`, `O` and `.` characters row by row into a new board.
+///
+/// # Safety
+///
+/// `code` must be a NUL-terminated string and `out` valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn ttt_board_from_code(
+    code: *const c_char,
+    out: *mut *mut Board,
+) -> TttStatus {
+    guard(|| {
+        let out = arg_mut(out)?;
+        let board = Board::from_code(arg_str(code)?).ok_or(TttStatus::InvalidBoard)?;
+        let crosses = board.field_state_count(FieldState::X);
+        let circles = board.field_state_count(FieldState::O);
+        if crosses != circles && crosses != circles + 1 {
+            return Err(TttStatus::InvalidBoard);
+        }
+        *out = Box::into_raw(Box::new(board));
+        Ok(())
+    })
+}
+
+/// Frees a board, null is ignored.
+///
+/// # Safety
+///
+/// `board` must be null or a board returned by this library and not yet freed.
+#[no_mangle]
+pub unsafe extern "C" fn ttt_board_free(board: *mut Board) {
+    if !board.is_null() {
+        drop(Box::from_raw(board));
+    }
+}
+
+/// Plays `field`, 0 to 8 row by row, for the side to move.
+///
+/// # Safety
+///
+/// `board` must be null or a valid board.
+#[no_mangle]
+pub unsafe extern "C" fn ttt_board_play(board: *mut Board, field: c_int) -> TttStatus {
+    guard(|| {
+        let board = arg_mut(board)?;
+        let pos = position(field)?;
+        if board.is_finished() {
+            return Err(TttStatus::GameOver);
+        }
+        if board.0[pos.y][pos.x] != FieldState::Empty {
+            return Err(TttStatus::FieldTaken);
+        }
+        board.play_move_at(pos);
+        Ok(())
+    })
+}
+
+/// Writes the mark on `field`: 0 for empty, 1 for X and 2 for O.
+///
+/// # Safety
+///
+/// `board` must be null or a valid board and `out` valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn ttt_board_field(
+    board: *const Board,
+    field: c_int,
+    out: *mut c_int,
+) -> TttStatus {
+    guard(|| {
+        let (board, out) = (arg(board)?, arg_mut(out)?);
+        let pos = position(field)?;
+        *out = board.0[pos.y][pos.x] as c_int;
+        Ok(())
+    })
+}
+
+/// Writes the side to move, 1 for X and 2 for O.
+///
+/// # Safety
+///
+/// `board` must be null or a valid board and `out` valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn ttt_board_next_player(
+    board: *const Board,
+    out: *mut c_int,
+) -> TttStatus {
+    guard(|| {
+        *arg_mut(out)? = arg(board)?.next_player() as c_int;
+        Ok(())
+    })
+}
+
+/// Writes the winner, 1 for X and 2 for O, or 0 if nobody has three in a row.
+///
+/// # Safety
+///
+/// `board` must be null or a valid board and `out` valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn ttt_board_winner(
+    board: *const Board,
+    out: *mut c_int,
+) -> TttStatus {
+    guard(|| {
+        *arg_mut(out)? = arg(board)?.find_winner() as c_int;
+        Ok(())
+    })
+}
+
+/// Writes 1 if somebody has won or the board is full, otherwise 0.
+///
+/// # Safety
+///
+/// `board` must be null or a valid board and `out` valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn ttt_board_is_finished(
+    board: *const Board,
+    out: *mut c_int,
+) -> TttStatus {
+    guard(|| {
+        *arg_mut(out)? = arg(board)?.is_finished() as c_int;
+        Ok(())
+    })
+}
+
+/// Writes the board as nine characters and a NUL terminator into `out`.
+///
+/// # Safety
+///
+/// `board` must be null or a valid board and `out` valid for 10 bytes of writes.
+#[no_mangle]
+pub unsafe extern "C" fn ttt_board_to_code(
+    board: *const Board,
+    out: *mut c_char,
+) -> TttStatus {
+    guard(|| {
+        let board = arg(board)?;
+        if out.is_null() {
+            return Err(TttStatus::NullPointer);
+        }
+        let code = board.to_code();
+        ptr::copy_nonoverlapping(code.as_ptr().cast::<c_char>(), out, code.len());
+        *out.add(code.len()) = 0;
+        Ok(())
+    })
+}
+
+/// Loads an agent saved by the binary, to be freed with [`ttt_agent_free`].
+///
+/// # Safety
+///
+/// `path` must be a NUL-terminated string and `out` valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn ttt_agent_load(
+    path: *const c_char,
+    out: *mut *mut SavedAgent,
+) -> TttStatus {
+    guard(|| {
+        let out = arg_mut(out)?;
+        let agent = SavedAgent::load(arg_str(path)?).map_err(|_| TttStatus::Io)?;
+        *out = Box::into_raw(Box::new(agent));
+        Ok(())
+    })
+}
+
+/// Frees an agent, null is ignored.
+///
+/// # Safety
+///
+/// `agent` must be null or an agent returned by this library and not yet freed.
+#[no_mangle]
+pub unsafe extern "C" fn ttt_agent_free(agent: *mut SavedAgent) {
+    if !agent.is_null() {
+        drop(Box::from_raw(agent));
+    }
+}
+
+/// Restarts the generator breaking ties between equally good moves.
+///
+/// # Safety
+///
+/// `agent` must be null or a valid agent.
+#[no_mangle]
+pub unsafe extern "C" fn ttt_agent_reseed(
+    agent: *mut SavedAgent,
+    seed: u64,
+) -> TttStatus {
+    guard(|| {
+        arg_mut(agent)?.reseed(seed);
+        Ok(())
+    })
+}
+
+/// Writes the agent's greedy move for the side to move on `board`, without
+/// playing it. Fails with [`TttStatus::NoMove`] if the agent has no move to an
+/// empty field.
+///
+/// # Safety
+///
+/// `agent` and `board` must be null or valid and `out` valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn ttt_agent_move(
+    agent: *mut SavedAgent,
+    board: *const Board,
+    out: *mut c_int,
+) -> TttStatus {
+    guard(|| {
+        let (agent, board, out) = (arg_mut(agent)?, arg(board)?, arg_mut(out)?);
+        if board.is_finished() {
+            return Err(TttStatus::GameOver);
+        }
+        let pos = agent
+            .learner()
+            .choose_move(board)
+            .filter(|pos| board.0[pos.y][pos.x] == FieldState::Empty)
+            .ok_or(TttStatus::NoMove)?;
+        *out = (pos.y * 3 + pos.x) as c_int;
+        Ok(())
+    })
+}
+
+/// Writes the value of every field into `out`: the bias of the moves a table agent
+/// knows or the network output of a neural agent, NaN for fields without a value.
+///
+/// # Safety
+///
+/// `agent` and `board` must be null or valid and `out` valid for 9 writes.
+#[no_mangle]
+pub unsafe extern "C" fn ttt_agent_move_values(
+    agent: *const SavedAgent,
+    board: *const Board,
+    out: *mut c_double,
+) -> TttStatus {
+    guard(|| {
+        let (agent, board) = (arg(agent)?, arg(board)?);
+        if out.is_null() {
+            return Err(TttStatus::NullPointer);
+        }
+        let values: Vec<(FieldPosition, f64)> = match agent {
+            SavedAgent::Table(agent) => agent
+                .get_actions_from_board(board)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|action| (action.field_pos, action.bias as f64))
+                .collect(),
+            SavedAgent::Neural(agent) => agent.move_values(board),
+        };
+        let out = std::slice::from_raw_parts_mut(out, 9);
+        out.fill(f64::NAN);
+        for (pos, value) in values {
+            out[pos.y * 3 + pos.x] = value;
+        }
+        Ok(())
+    })
+}
+
+#[test]
+fn ffi_test() {
+    let path = std::env::temp_dir().join(format!("ttt-ffi-{}.agent", std::process::id()));
+    std::fs::write(&path, "agent 0 0.999 0.1\n......... 1,1:6:2 0,0:-3:1\n").unwrap();
+    let c_path = std::ffi::CString::new(path.to_str().unwrap()).unwrap();
+
+    unsafe {
+        let mut agent = ptr::null_mut();
+        assert_eq!(ttt_agent_load(c_path.as_ptr(), &mut agent), TttStatus::Ok);
+        let board = ttt_board_new();
+        let mut field = -1;
+        assert_eq!(ttt_agent_move(agent, board, &mut field), TttStatus::Ok);
+        assert_eq!(field, 4);
+        let mut values = [0.0; 9];
+        assert_eq!(
+            ttt_agent_move_values(agent, board, values.as_mut_ptr()),
+            TttStatus::Ok
+        );
+        assert_eq!((values[4], values[0]), (6.0, -3.0));
+        assert!(values[8].is_nan());
+
+        assert_eq!(ttt_board_play(board, field), TttStatus::Ok);
+        assert_eq!(ttt_board_play(board, field), TttStatus::FieldTaken);
+        assert_eq!(ttt_board_play(board, 9), TttStatus::InvalidField);
+        assert_eq!(ttt_board_play(ptr::null_mut(), 0), TttStatus::NullPointer);
+        let mut code = [0 as c_char; 10];
+        assert_eq!(ttt_board_to_code(board, code.as_mut_ptr()), TttStatus::Ok);
+        assert_eq!(CStr::from_ptr(code.as_ptr()), c"....X....");
+        let mut side = 0;
+        assert_eq!(ttt_board_next_player(board, &mut side), TttStatus::Ok);
+        assert_eq!(side, FieldState::O as c_int);
+        ttt_board_free(board);
+
+        let mut won = ptr::null_mut();
+        assert_eq!(
+            ttt_board_from_code(c"XXXOO....".as_ptr(), &mut won),
+            TttStatus::Ok
+        );
+        let (mut winner, mut finished) = (0, 0);
+        assert_eq!(ttt_board_winner(won, &mut winner), TttStatus::Ok);
+        assert_eq!(ttt_board_is_finished(won, &mut finished), TttStatus::Ok);
+        assert_eq!((winner, finished), (FieldState::X as c_int, 1));
+        assert_eq!(ttt_board_play(won, 8), TttStatus::GameOver);
+        assert_eq!(ttt_agent_move(agent, won, &mut field), TttStatus::GameOver);
+        ttt_board_free(won);
+
+        let mut invalid = ptr::null_mut();
+        let status = ttt_board_from_code(c"XXX......".as_ptr(), &mut invalid);
+        assert_eq!(status, TttStatus::InvalidBoard);
+        assert!(invalid.is_null());
+        let message = CStr::from_ptr(ttt_status_message(status as c_int));
+        assert_eq!(message, c"invalid board");
+        ttt_agent_free(agent);
+    }
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn header_test() {
+    let header = include_str!("../include/tic_tac_toe.h");
+    let value = |name: &str| -> i64 {
+        header
+            .lines()
+            .map(|line| line.trim().trim_end_matches(','))
+            .find_map(|line| {
+                let rest = line.strip_prefix(name)?;
+                let rest = rest.strip_prefix(" = ").or(rest.strip_prefix(' '))?;
+                rest.parse().ok()
+            })
+            .unwrap_or_else(|| panic!("`{}` is missing in the header", name))
+    };
+    let statuses = [
+        ("TTT_OK", TttStatus::Ok),
+        ("TTT_NULL_POINTER", TttStatus::NullPointer),
+        ("TTT_INVALID_FIELD", TttStatus::InvalidField),
+        ("TTT_FIELD_TAKEN", TttStatus::FieldTaken),
+        ("TTT_GAME_OVER", TttStatus::GameOver),
+        ("TTT_INVALID_BOARD", TttStatus::InvalidBoard),
+        ("TTT_INVALID_UTF8", TttStatus::InvalidUtf8),
+        ("TTT_IO", TttStatus::Io),
+        ("TTT_PANIC", TttStatus::Panic),
+        ("TTT_NO_MOVE", TttStatus::NoMove),
+    ];
+    for (name, status) in statuses {
+        assert_eq!(value(name), status as i64, "{}", name);
+    }
+    assert_eq!(header.matches(" = ").count(), statuses.len());
+    assert_eq!(STATUS_MESSAGES.len(), statuses.len());
+    assert_eq!(value("#define TTT_ABI_VERSION"), TTT_ABI_VERSION as i64);
+    assert_eq!(value("#define TTT_EMPTY"), FieldState::Empty as i64);
+    assert_eq!(value("#define TTT_X"), FieldState::X as i64);
+    assert_eq!(value("#define TTT_O"), FieldState::O as i64);
+}
@@ -9,6 +9,7 @@ use crate::board::{Board, FieldState};
 ///  [4,5,6],
 ///  [7,8,9]]
 ///
+#[derive(Default)]
 pub struct GameState {}
 
 impl GameState {
@@ -22,35 +23,17 @@ impl GameState {
     {
         let mut board = Board::EMPTY;
         loop {
-            let event = match board.find_winner() {
-                FieldState::X => GameEvent::CrossWon,
-                FieldState::O => GameEvent::CircleWon,
-                _ => {
-                    let cross_count = board.field_state_count(FieldState::X);
-                    let circle_count = board.field_state_count(FieldState::O);
-                    let empty_field_count = board.field_state_count(FieldState::Empty);
-                    if empty_field_count == 0 {
-                        GameEvent::Draw
-                    } else if cross_count == circle_count {
-                        GameEvent::CrossTurn
-                    } else if cross_count == (circle_count + 1) {
-                        GameEvent::CircleTurn
-                    } else {
-                        GameEvent::InvalidBoard
-                    }
-                }
-            };
+            let event = GameEvent::of(&board);
             event_handler(event, &mut board);
 
-            match event {
-                GameEvent::CrossWon | GameEvent::CircleWon | GameEvent::Draw => break,
-                _ => (),
+            if event.is_finished() {
+                break;
             }
         }
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum GameEvent {
     CrossWon,
     CircleWon,
@@ -59,3 +42,47 @@ pub enum GameEvent {
     CircleTurn,
     InvalidBoard,
 }
+
+impl GameEvent {
+    /// Returns the state `board` is in: who won, a draw or whose turn it is.
+    pub fn of(board: &Board) -> Self {
+        match board.find_winner() {
+            FieldState::X => GameEvent::CrossWon,
+            FieldState::O => GameEvent::CircleWon,
+            _ => {
+                let cross_count = board.field_state_count(FieldState::X);
+                let circle_count = board.field_state_count(FieldState::O);
+                let empty_field_count = board.field_state_count(FieldState::Empty);
+                if empty_field_count == 0 {
+                    GameEvent::Draw
+                } else if cross_count == circle_count {
+                    GameEvent::CrossTurn
+                } else if cross_count == (circle_count + 1) {
+                    GameEvent::CircleTurn
+                } else {
+                    GameEvent::InvalidBoard
+                }
+            }
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        matches!(
+            self,
+            GameEvent::CrossWon | GameEvent::CircleWon | GameEvent::Draw
+        )
+    }
+}
+
+impl std::fmt::Display for GameEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GameEvent::CrossWon => write!(f, "X won"),
+            GameEvent::CircleWon => write!(f, "O won"),
+            GameEvent::Draw => write!(f, "draw"),
+            GameEvent::CrossTurn => write!(f, "X to move"),
+            GameEvent::CircleTurn => write!(f, "O to move"),
+            GameEvent::InvalidBoard => write!(f, "invalid"),
+        }
+    }
+}
@@ -0,0 +1,162 @@
+use crate::afterstate::AfterstateAgent;
+use crate::agent::{Agent, AgentConfig};
+use crate::double_q::DoubleQAgent;
+use crate::mlp::Optimizer;
+use crate::neural::NeuralAgent;
+use crate::player::Learner;
+use crate::policy::{PolicyAgent, PolicyMethod, PolicyModel};
+use crate::replay::ReplayConfig;
+use crate::td::{TdAgent, TdMethod, TraceKind};
+
+/// The kinds of learners which can be trained through the [`Learner`] trait.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LearnerKind {
+    /// The table [`Agent`] summing rewards into action biases.
+    Agent,
+    Afterstate,
+    Td(TdMethod),
+    DoubleQ,
+    /// Q-learning with planning from a learned model.
+    DynaQ,
+    /// Q-learning with a neural network instead of a table.
+    Neural,
+    Policy(PolicyMethod),
+}
+
+/// Settings of the learners beyond the exploration settings in [`AgentConfig`].
+/// Each learner ignores the ones it has no use for.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LearnerParams {
+    pub learning_rate: f64,
+    /// Trace decay of the TD learners and the agent, `None` keeps their default:
+    /// one-step TD and full credit for every move of the agent.
+    pub lambda: Option<f64>,
+    pub trace_kind: TraceKind,
+    /// Experience replay of the TD learners, off if `None`.
+    pub replay: Option<ReplayConfig>,
+    /// Simulated updates per real move of Dyna-Q.
+    pub planning_steps: usize,
+    /// Hidden layer size of the neural learner.
+    pub hidden_units: usize,
+    pub optimizer: Optimizer,
+    /// Whether policy-gradient learners use networks instead of tables.
+    pub neural_policy: bool,
+}
+
+impl Default for LearnerParams {
+    fn default() -> Self {
+        Self {
+            learning_rate: 0.1,
+            lambda: None,
+            trace_kind: TraceKind::Replacing,
+            replay: None,
+            planning_steps: 10,
+            hidden_units: 64,
+            optimizer: Optimizer::Adam {
+                learning_rate: 0.001,
+            },
+            neural_policy: false,
+        }
+    }
+}
+
+impl LearnerKind {
+    pub fn build(
+        &self,
+        config: AgentConfig,
+        params: LearnerParams,
+        seed: u64,
+    ) -> Box<dyn Learner> {
+        match self {
+            LearnerKind::Agent => {
+                let agent = Agent::new_seeded(config, seed);
+                match params.lambda {
+                    Some(lambda) => Box::new(agent.with_traces(lambda)),
+                    None => Box::new(agent),
+                }
+            }
+            LearnerKind::Afterstate => Box::new(AfterstateAgent::new_seeded(
+                config,
+                params.learning_rate,
+                seed,
+            )),
+            LearnerKind::Td(method) => {
+                let agent =
+                    TdAgent::new_seeded(*method, config, params.learning_rate, seed);
+                let agent = match params.lambda {
+                    Some(lambda) => agent.with_traces(lambda, params.trace_kind),
+                    None => agent,
+                };
+                match params.replay {
+                    Some(replay) => Box::new(agent.with_replay(replay)),
+                    None => Box::new(agent),
+                }
+            }
+            LearnerKind::DoubleQ => {
+                Box::new(DoubleQAgent::new_seeded(config, params.learning_rate, seed))
+            }
+            LearnerKind::DynaQ => Box::new(
+                TdAgent::new_seeded(
+                    TdMethod::QLearning,
+                    config,
+                    params.learning_rate,
+                    seed,
+                )
+                .with_planning(params.planning_steps),
+            ),
+            LearnerKind::Neural => Box::new(NeuralAgent::new_seeded(
+                config,
+                params.hidden_units,
+                params.optimizer,
+                seed,
+            )),
+            LearnerKind::Policy(method) => {
+                let model = if params.neural_policy {
+                    PolicyModel::Neural {
+                        hidden_units: params.hidden_units,
+                        optimizer: params.optimizer,
+                    }
+                } else {
+                    PolicyModel::Table {
+                        learning_rate: params.learning_rate,
+                    }
+                };
+                Box::new(PolicyAgent::new_seeded(*method, model, seed))
+            }
+        }
+    }
+}
+
+impl std::str::FromStr for LearnerKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "agent" => Ok(LearnerKind::Agent),
+            "afterstate" => Ok(LearnerKind::Afterstate),
+            "q-learning" => Ok(LearnerKind::Td(TdMethod::QLearning)),
+            "sarsa" => Ok(LearnerKind::Td(TdMethod::Sarsa)),
+            "expected-sarsa" => Ok(LearnerKind::Td(TdMethod::ExpectedSarsa)),
+            "double-q" => Ok(LearnerKind::DoubleQ),
+            "dyna-q" => Ok(LearnerKind::DynaQ),
+            "neural" => Ok(LearnerKind::Neural),
+            "reinforce" => Ok(LearnerKind::Policy(PolicyMethod::Reinforce)),
+            "actor-critic" => Ok(LearnerKind::Policy(PolicyMethod::ActorCritic)),
+            _ => Err(format!("unknown learner `{}`", s)),
+        }
+    }
+}
+
+impl std::fmt::Display for LearnerKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LearnerKind::Agent => write!(f, "agent"),
+            LearnerKind::Afterstate => write!(f, "afterstate"),
+            LearnerKind::Td(method) => write!(f, "{}", method),
+            LearnerKind::DoubleQ => write!(f, "double-q"),
+            LearnerKind::DynaQ => write!(f, "dyna-q"),
+            LearnerKind::Neural => write!(f, "neural"),
+            LearnerKind::Policy(method) => write!(f, "{}", method),
+        }
+    }
+}
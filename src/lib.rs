@@ -0,0 +1,39 @@
+pub mod afterstate;
+pub mod agent;
+pub mod alphazero;
+pub mod board;
+pub mod curriculum;
+pub mod decision;
+pub mod dot;
+pub mod double_q;
+pub mod dyna;
+pub mod engine;
+pub mod env;
+pub mod evaluation;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod game;
+pub mod learners;
+pub mod mcts;
+pub mod memory;
+pub mod minimax;
+pub mod mlp;
+pub mod neural;
+pub mod opponent_model;
+pub mod player;
+pub mod policy;
+#[cfg(feature = "python")]
+pub mod python;
+pub mod replay;
+pub mod report;
+pub mod saved;
+#[cfg(feature = "server")]
+pub mod server;
+pub mod sweep;
+pub mod td;
+pub mod tournament;
+pub mod training;
+#[cfg(feature = "tui")]
+pub mod tui;
+#[cfg(feature = "wasm")]
+pub mod wasm;
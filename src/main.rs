@@ -1,11 +1,1055 @@
-use agent::Agent;
-use game::{GameEvent, GameState};
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+use std::str::FromStr;
+use tic_tac_toe_learning_ai::agent::{Agent, AgentConfig, Rewards};
+use tic_tac_toe_learning_ai::alphazero::{self, AlphaZeroAgent, AlphaZeroConfig};
+use tic_tac_toe_learning_ai::board::{Board, FieldPosition, FieldState};
+use tic_tac_toe_learning_ai::curriculum::{self, CurriculumConfig};
+use tic_tac_toe_learning_ai::dot::{self, DotOptions};
+use tic_tac_toe_learning_ai::engine::{self, Engine};
+use tic_tac_toe_learning_ai::evaluation::{self, EvaluationResult};
+use tic_tac_toe_learning_ai::game::{GameEvent, GameState};
+use tic_tac_toe_learning_ai::learners::{LearnerKind, LearnerParams};
+use tic_tac_toe_learning_ai::minimax::{Difficulty, PerfectPlayer};
+use tic_tac_toe_learning_ai::neural::NeuralAgent;
+use tic_tac_toe_learning_ai::opponent_model::{self, OpponentModelAgent};
+use tic_tac_toe_learning_ai::player;
+use tic_tac_toe_learning_ai::player::{Player, RandomPlayer};
+use tic_tac_toe_learning_ai::policy::PolicyModel;
+use tic_tac_toe_learning_ai::report::{self, ReportConfig};
+use tic_tac_toe_learning_ai::saved::SavedAgent;
+use tic_tac_toe_learning_ai::sweep::{self, SweepConfig};
+use tic_tac_toe_learning_ai::tournament::{self, Entrant};
+use tic_tac_toe_learning_ai::training::{self, TrainingStats};
 
-mod agent;
-mod board;
-mod game;
+const USAGE: &str = "\
+Usage:
+    tic-tac-toe-learning-ai                      train two agents and step through their games
+    tic-tac-toe-learning-ai play [OPTIONS]       play against the computer
+    tic-tac-toe-learning-ai train [OPTIONS]      train an agent and evaluate or save it
+    tic-tac-toe-learning-ai curriculum [OPTIONS] train an agent against a changing opponent pool
+    tic-tac-toe-learning-ai alphazero [OPTIONS]  train a search guided by learned priors and values
+    tic-tac-toe-learning-ai exploit [OPTIONS]    model a flawed opponent and play a best response
+    tic-tac-toe-learning-ai engine [OPTIONS]     answer engine protocol commands on stdin
+    tic-tac-toe-learning-ai dot [OPTIONS]        export the game tree of a saved agent for Graphviz
+    tic-tac-toe-learning-ai report [OPTIONS]     train an agent pair and write an HTML report
+    tic-tac-toe-learning-ai sweep [OPTIONS]      run a hyperparameter sweep
+    tic-tac-toe-learning-ai tournament [OPTIONS] rate saved agents and scripted players
+    tic-tac-toe-learning-ai tui [OPTIONS]        play or watch games in a full-screen terminal UI
+
+Play options:
+    --difficulty <LEVEL>  easy, medium, hard or perfect   [default: medium]
+    --agent <FILE>        play against a saved agent instead
+    --as <SIDE>           play as x or o                  [default: x]
+
+Train options:
+    --learner <KIND>      agent, afterstate, q-learning, sarsa,
+                          expected-sarsa, double-q, dyna-q, neural,
+                          reinforce or actor-critic       [default: agent]
+    --learning-rate <F>   step size of value learners     [default: 0.1]
+    --lambda <F>          trace decay of TD learners and the agent, 0 is
+                          one-step TD and 1 is Monte Carlo
+                          [default: 0, 1 for agent]
+    --trace <KIND>        accumulating or replacing traces [default: replacing]
+    --replay <N>          replay up to N past transitions of TD learners
+                          after every game                [default: 10000 if
+                          any replay option is given, off otherwise]
+    --replay-ratio <F>    replayed per newly played move  [default: 1]
+    --priority <F>        prioritized replay exponent, 0 is uniform [default: 0]
+    --planning <N>        simulated updates per move of dyna-q [default: 10]
+    --model <KIND>        `table` or `neural` parameters of reinforce and
+                          actor-critic                    [default: table]
+    --hidden <N>          hidden units of neural networks [default: 64]
+    --optimizer <OPT>     sgd or adam, optionally with `:<learning rate>`
+                          [default: adam:0.001]
+    --out <FILE>          save the trained agent (`agent` and `neural` only)
+    --games <N>           training games                  [default: 100000]
+    --opponent <SPEC>     train against a scripted player (see --player below)
+                          instead of a second agent
+    --table <KIND>        `separate` tables for X and O, or one `shared` table
+                          learned from both sides of every game (`agent` only)
+                          [default: separate]
+    --epsilon <F>         initial exploration rate        [default: 0.6]
+    --decay <F>           epsilon decay per win           [default: 0.999]
+    --min-epsilon <F>     lowest exploration rate         [default: 0.1]
+    --seed <N>            seed, random if not given
+
+Curriculum options:
+    --rounds <N>          training rounds                     [default: 50]
+    --games <N>           training games per round            [default: 2000]
+    --eval-games <N>      games per side vs perfect per round [default: 50]
+    --snapshot-every <N>  rounds between frozen snapshots     [default: 5]
+    --max-snapshots <N>   snapshots kept in the pool          [default: 5]
+    --epsilon <F>         initial exploration rate            [default: 0.6]
+    --seed <N>            seed                                [default: 0]
+    --out <FILE>          save the trained agent
+
+AlphaZero options:
+    --iterations <N>      self-play and training iterations   [default: 20]
+    --games <N>           self-play games per iteration       [default: 50]
+    --simulations <N>     search iterations per move          [default: 50]
+    --epochs <N>          training passes per iteration       [default: 4]
+    --window <N>          train on the positions of the latest N iterations
+                                                              [default: 4]
+    --model <KIND>        `table` or `neural` priors and values [default: table]
+    --learning-rate <F>   step size of the table              [default: 0.3]
+    --hidden <N>          hidden units of the networks        [default: 64]
+    --optimizer <OPT>     optimizer of the networks           [default: adam:0.001]
+    --eval-games <N>      games per side vs perfect per iteration [default: 10]
+    --seed <N>            seed                                [default: 0]
+
+Exploit options:
+    --opponent <SPEC>     player to exploit: a saved agent file or a scripted
+                          player (see --player below)  [default: an agent
+                          trained by self-play for --opponent-games games]
+    --opponent-games <N>  self-play games of the default opponent [default: 2000]
+    --games <N>           games to model the opponent         [default: 1000]
+    --eval-games <N>      games per side against the opponent [default: 500]
+    --prior <F>           pseudo-games per move of unseen replies [default: 1]
+    --moves <KIND>        `safe` moves which keep the minimax result, or `any`
+                          move the model favors               [default: safe]
+    --seed <N>            seed                                [default: 0]
+
+Engine options:
+    --agent <FILE>        saved agent to drive  [default: a new agent which learns
+                          from the results it is told]
+    --seed <N>            seed, random if not given
+    Commands, answered by one line `= <answer>` or `? <error>`: newgame,
+    position startpos|<board> [moves <field> ...], play <field>, go,
+    setoption epsilon|seed <value>, result win|loss|draw, show, save <file>,
+    isready, list_commands, quit. Fields are numbered 1 to 9 row by row.
+
+Dot options:
+    --agent <FILE>        saved agent to export
+    --depth <N>           moves from the empty board to export [default: 9]
+    --min-visits <N>      leave out moves played in fewer games [default: 0]
+    --out <FILE>          write the graph to a file instead of stdout
+
+Report options:
+    --games <N>           training games                      [default: 50000]
+    --checkpoints <N>     evaluations during training         [default: 25]
+    --eval-games <N>      games per side against each opponent [default: 100]
+    --epsilon <F>         initial exploration rate            [default: 0.6]
+    --decay <F>           epsilon decay per win               [default: 0.999]
+    --min-epsilon <F>     lowest exploration rate             [default: 0.1]
+    --seed <N>            seed                                [default: 0]
+    --out <FILE>          write the report to a file instead of stdout
+
+Sweep options (values are lists `a,b,c` or ranges `start:end:step`):
+    --learner <KIND>      kind of learner, as for train   [default: agent]
+    --win <GRID>          reward for a win                [default: 6]
+    --loss <GRID>         reward for a loss               [default: -3]
+    --draw <GRID>         reward for a draw               [default: 1]
+    --epsilon <GRID>      initial exploration rate        [default: 0.6]
+    --decay <GRID>        epsilon decay per win           [default: 0.999]
+    --min-epsilon <GRID>  lowest exploration rate         [default: 0.1]
+    --learning-rate <GRID> step size of value learners    [default: 0.1]
+    --lambda <GRID>       trace decay of TD learners and the agent
+                          [default: 0, 1 for agent]
+    --trace <KIND>        accumulating or replacing traces [default: replacing]
+    --games <N>           training games per agent        [default: 20000]
+    --eval-games <N>      games per side vs perfect       [default: 100]
+    --seed <N>            base seed                       [default: 0]
+    --out <FILE>          write the table to a file instead of stdout
+
+Tournament options:
+    --agents <DIR>        load every `*.agent` file of a directory, repeatable
+    --player <SPEC>       add a scripted player, repeatable: random, heuristic,
+                          perfect, easy, medium, hard,
+                          minimax[:<depth>[:<blunder>]] or mcts[:<iterations>]
+    --games <N>           games per pairing and color         [default: 10]
+    --seed <N>            base seed                           [default: 0]
+    --out <FILE>          write the report to a file instead of stdout
+
+Tui options (sides are `human`, a saved agent file or a scripted player):
+    --x <SIDE>            who plays X                         [default: human]
+    --o <SIDE>            who plays O                         [default: medium]
+    --delay <MS>          milliseconds between computer moves [default: 500]
+    --seed <N>            seed, random if not given";
 
 fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let result = match args.first().map(String::as_str) {
+        None => {
+            watch_self_play();
+            Ok(())
+        }
+        Some("play") => run_play(&args[1..]),
+        Some("train") => run_train(&args[1..]),
+        Some("curriculum") => run_curriculum(&args[1..]),
+        Some("alphazero") => run_alphazero(&args[1..]),
+        Some("exploit") => run_exploit(&args[1..]),
+        Some("engine") => run_engine(&args[1..]),
+        Some("dot") => run_dot(&args[1..]),
+        Some("report") => run_report(&args[1..]),
+        Some("sweep") => run_sweep(&args[1..]),
+        Some("tournament") => run_tournament(&args[1..]),
+        #[cfg(feature = "tui")]
+        Some("tui") => run_tui(&args[1..]),
+        Some("help" | "--help" | "-h") => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+        Some(command) => Err(format!("unknown command `{}`", command)),
+    };
+    if let Err(err) = result {
+        eprintln!("error: {}\n\n{}", err, USAGE);
+        std::process::exit(1);
+    }
+}
+
+/// Splits `--option value` arguments into pairs.
+fn option_pairs(args: &[String]) -> Result<Vec<(&str, &str)>, String> {
+    let mut pairs = Vec::new();
+    let mut args = args.iter();
+    while let Some(option) = args.next() {
+        let value = args
+            .next()
+            .ok_or_else(|| format!("missing value for `{}`", option))?;
+        pairs.push((option.as_str(), value.as_str()));
+    }
+    Ok(pairs)
+}
+
+fn parse_value<T: FromStr>(option: &str, value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("invalid value `{}` for `{}`", value, option))
+}
+
+/// Parses a size such as a number of hidden units, which cannot be zero.
+fn parse_size(option: &str, value: &str) -> Result<usize, String> {
+    match parse_value(option, value)? {
+        0 => Err(format!("`{}` has to be at least 1", option)),
+        size => Ok(size),
+    }
+}
+
+/// Parses a finite number of at least 0.
+fn parse_non_negative(option: &str, value: &str) -> Result<f64, String> {
+    let number: f64 = parse_value(option, value)?;
+    if !number.is_finite() || number < 0.0 {
+        return Err(format!("`{}` has to be a number of at least 0", option));
+    }
+    Ok(number)
+}
+
+/// Parses a rate such as an exploration rate, which has to lie between 0 and 1.
+fn parse_rate(option: &str, value: &str) -> Result<f64, String> {
+    let rate: f64 = parse_value(option, value)?;
+    if !(0.0..=1.0).contains(&rate) {
+        return Err(format!("`{}` has to be between 0 and 1", option));
+    }
+    Ok(rate)
+}
+
+/// Parses a sweep grid of rates, see [`parse_rate`].
+fn parse_rate_grid(option: &str, value: &str) -> Result<Vec<f64>, String> {
+    let rates: Vec<f64> = sweep::parse_grid(value)?;
+    if rates.iter().any(|rate| !(0.0..=1.0).contains(rate)) {
+        return Err(format!("`{}` has to be between 0 and 1", option));
+    }
+    Ok(rates)
+}
+
+/// Writes to the file at `out` or to stdout if no file was given.
+fn write_output<F>(out: Option<&str>, write: F) -> Result<(), String>
+where
+    F: FnOnce(&mut dyn std::io::Write) -> std::io::Result<()>,
+{
+    match out {
+        Some(path) => File::create(path)
+            .and_then(|mut file| write(&mut file))
+            .map_err(|err| format!("failed to write `{}`: {}", path, err)),
+        None => write(&mut std::io::stdout()).map_err(|err| err.to_string()),
+    }
+}
+
+fn run_play(args: &[String]) -> Result<(), String> {
+    let mut difficulty = Difficulty::Medium;
+    let mut agent_path = None;
+    let mut human_side = FieldState::X;
+    for (option, value) in option_pairs(args)? {
+        match option {
+            "--difficulty" => difficulty = value.parse()?,
+            "--agent" => agent_path = Some(value),
+            "--as" => {
+                human_side = match value.to_ascii_lowercase().as_str() {
+                    "x" => FieldState::X,
+                    "o" => FieldState::O,
+                    _ => {
+                        return Err(format!("invalid side `{}`, expected x or o", value))
+                    }
+                }
+            }
+            _ => return Err(format!("unknown option `{}`", option)),
+        }
+    }
+    let (computer, name): (Box<dyn Player>, String) = match agent_path {
+        Some(path) => (
+            load_player(Path::new(path), rand::random())?,
+            format!("agent `{}`", path),
+        ),
+        None => (
+            Box::new(difficulty.player()),
+            format!("{} computer", difficulty),
+        ),
+    };
+
+    println!("Playing as {} against the {}.", human_side, name);
+    println!("Enter the number of a field to play there:\n");
+    let mut human = HumanPlayer;
+    let mut computer = ComputerPlayer(computer);
+    let mut result = EvaluationResult::default();
+    loop {
+        let event = if human_side == FieldState::X {
+            evaluation::play_game(&mut human, &mut computer)
+        } else {
+            evaluation::play_game(&mut computer, &mut human)
+        };
+        match (event, human_side) {
+            (GameEvent::CrossWon, FieldState::X)
+            | (GameEvent::CircleWon, FieldState::O) => {
+                println!("You won!");
+                result.wins += 1;
+            }
+            (GameEvent::Draw, _) => {
+                println!("It's a draw!");
+                result.draws += 1;
+            }
+            _ => {
+                println!("You lost!");
+                result.losses += 1;
+            }
+        }
+        println!("Your record (W/D/L): {}\n", result);
+        if !prompt("Play again? [Y/n] ").is_some_and(|answer| {
+            let answer = answer.trim().to_ascii_lowercase();
+            answer.is_empty() || answer == "y" || answer == "yes"
+        }) {
+            return Ok(());
+        }
+    }
+}
+
+/// Reads moves from stdin, fields are numbered 1 to 9 row by row.
+struct HumanPlayer;
+
+impl Player for HumanPlayer {
+    fn choose_move(&mut self, board: &Board) -> Option<FieldPosition> {
+        println!("{}", numbered_board(board));
+        loop {
+            let line = match prompt(&format!("{} to play: ", board.next_player())) {
+                Some(line) => line,
+                None => std::process::exit(0),
+            };
+            let pos = match line.trim().parse::<usize>() {
+                Ok(n @ 1..=9) => FieldPosition::new((n - 1) % 3, (n - 1) / 3),
+                _ => {
+                    println!("Enter a number from 1 to 9.");
+                    continue;
+                }
+            };
+            if board.0[pos.y][pos.x] != FieldState::Empty {
+                println!("That field is already taken.");
+                continue;
+            }
+            let mut next = board.clone();
+            next.play_move_at(pos);
+            if next.is_finished() {
+                println!("{}", next);
+            }
+            return Some(pos);
+        }
+    }
+}
+
+/// Announces the moves of the wrapped player.
+struct ComputerPlayer(Box<dyn Player>);
+
+impl Player for ComputerPlayer {
+    fn choose_move(&mut self, board: &Board) -> Option<FieldPosition> {
+        let pos = self.0.choose_move(board)?;
+        println!("Computer plays {}\n", pos.y * 3 + pos.x + 1);
+        let mut next = board.clone();
+        next.play_move_at(pos);
+        if next.is_finished() {
+            println!("{}", next);
+        }
+        Some(pos)
+    }
+}
+
+/// Shows the board with the number of every empty field in its place.
+fn numbered_board(board: &Board) -> String {
+    let rows: Vec<String> = (0..3)
+        .map(|y| {
+            let fields: Vec<String> = (0..3)
+                .map(|x| match board.0[y][x] {
+                    FieldState::Empty => (y * 3 + x + 1).to_string(),
+                    field => field.to_string(),
+                })
+                .collect();
+            format!(" {}", fields.join(" | "))
+        })
+        .collect();
+    rows.join("\n------------\n")
+}
+
+/// Prints `message` and reads a line from stdin, `None` at the end of input.
+fn prompt(message: &str) -> Option<String> {
+    print!("{}", message);
+    std::io::stdout().flush().ok()?;
+    let mut line = String::new();
+    match std::io::stdin().read_line(&mut line) {
+        Ok(0) | Err(_) => None,
+        Ok(_) => Some(line),
+    }
+}
+
+fn run_train(args: &[String]) -> Result<(), String> {
+    let mut config = AgentConfig::default();
+    let mut learner = LearnerKind::Agent;
+    let mut params = LearnerParams::default();
+    let mut games = 100_000;
+    let mut seed = None;
+    let mut opponent = None;
+    let mut shared = false;
+    let mut out = None;
+    for (option, value) in option_pairs(args)? {
+        match option {
+            "--learner" => learner = value.parse()?,
+            "--learning-rate" => params.learning_rate = parse_value(option, value)?,
+            "--lambda" => params.lambda = Some(parse_rate(option, value)?),
+            "--trace" => params.trace_kind = value.parse()?,
+            "--replay" => {
+                let replay = params.replay.get_or_insert_with(Default::default);
+                replay.capacity = parse_value(option, value)?;
+            }
+            "--replay-ratio" => {
+                let ratio = parse_non_negative(option, value)?;
+                let replay = params.replay.get_or_insert_with(Default::default);
+                replay.sample_ratio = ratio;
+            }
+            "--priority" => {
+                let exponent = parse_non_negative(option, value)?;
+                let replay = params.replay.get_or_insert_with(Default::default);
+                replay.priority_exponent = exponent;
+            }
+            "--planning" => params.planning_steps = parse_value(option, value)?,
+            "--model" => {
+                params.neural_policy = match value {
+                    "neural" => true,
+                    "table" => false,
+                    _ => return Err(format!("invalid model `{}`", value)),
+                }
+            }
+            "--hidden" => params.hidden_units = parse_size(option, value)?,
+            "--optimizer" => params.optimizer = value.parse()?,
+            "--games" => games = parse_value(option, value)?,
+            "--opponent" => opponent = Some(value),
+            "--table" => {
+                shared = match value {
+                    "shared" => true,
+                    "separate" => false,
+                    _ => return Err(format!("invalid table `{}`", value)),
+                }
+            }
+            "--epsilon" => config.epsilon = parse_rate(option, value)?,
+            "--decay" => config.epsilon_decay = parse_rate(option, value)?,
+            "--min-epsilon" => config.min_epsilon = parse_rate(option, value)?,
+            "--seed" => seed = Some(parse_value::<u64>(option, value)?),
+            "--out" => out = Some(value),
+            _ => return Err(format!("unknown option `{}`", option)),
+        }
+    }
+    let seed = seed.unwrap_or_else(rand::random);
+    let mut opponent = match opponent {
+        Some(spec) => Some((spec, player::from_spec(spec, seed.wrapping_add(2))?)),
+        None => None,
+    };
+
+    if learner == LearnerKind::Agent {
+        let mut agent_cross = Agent::new_seeded(config, seed);
+        if let Some(lambda) = params.lambda {
+            agent_cross = agent_cross.with_traces(lambda);
+        }
+        if shared {
+            agent_cross = agent_cross.with_shared_table();
+        }
+        if let Some((spec, opponent)) = &mut opponent {
+            let result = training::train_against(
+                &mut agent_cross,
+                opponent.as_mut(),
+                Rewards::default(),
+                games,
+            );
+            println!("Results against {} (W/D/L): {}", spec, result);
+        } else if shared {
+            let stats = training::train_shared_self_play(
+                &mut agent_cross,
+                Rewards::default(),
+                games,
+            );
+            print_training_stats(stats);
+        } else {
+            let mut agent_circle = Agent::new_seeded(config, seed.wrapping_add(1))
+                .with_traces(agent_cross.lambda());
+            let stats = training::train_self_play(
+                &mut agent_cross,
+                &mut agent_circle,
+                Rewards::default(),
+                games,
+            );
+            print_training_stats(stats);
+            agent_cross.merge(&agent_circle);
+        }
+        print_strength(&mut agent_cross, None, seed);
+        if let Some(out) = out {
+            agent_cross
+                .save(out)
+                .map_err(|err| format!("failed to save `{}`: {}", out, err))?;
+            println!(
+                "Saved {} memorized boards to `{}`",
+                agent_cross.memorized_boards_count(),
+                out
+            );
+        }
+        return Ok(());
+    }
+
+    if learner == LearnerKind::Neural {
+        // The network sees boards from the side to move, so one agent plays both
+        let mut agent =
+            NeuralAgent::new_seeded(config, params.hidden_units, params.optimizer, seed);
+        if let Some((spec, opponent)) = &mut opponent {
+            let result = training::train_against(
+                &mut agent,
+                opponent.as_mut(),
+                Rewards::default(),
+                games,
+            );
+            println!("Results against {} (W/D/L): {}", spec, result);
+        } else {
+            let stats =
+                training::train_shared_self_play(&mut agent, Rewards::default(), games);
+            print_training_stats(stats);
+        }
+        print_strength(&mut agent, None, seed);
+        if let Some(out) = out {
+            agent
+                .save(out)
+                .map_err(|err| format!("failed to save `{}`: {}", out, err))?;
+            println!("Saved the network to `{}`", out);
+        }
+        return Ok(());
+    }
+
+    if out.is_some() {
+        return Err(format!("`{}` learners cannot be saved yet", learner));
+    }
+    let mut learner_cross = learner.build(config, params, seed);
+    if let Some((spec, opponent)) = &mut opponent {
+        let result = training::train_against(
+            learner_cross.as_mut(),
+            opponent.as_mut(),
+            Rewards::default(),
+            games,
+        );
+        println!("Results against {} (W/D/L): {}", spec, result);
+        print_strength(learner_cross.as_mut(), None, seed);
+    } else {
+        let mut learner_circle = learner.build(config, params, seed.wrapping_add(1));
+        let stats = training::train_self_play(
+            learner_cross.as_mut(),
+            learner_circle.as_mut(),
+            Rewards::default(),
+            games,
+        );
+        print_training_stats(stats);
+        print_strength(learner_cross.as_mut(), Some(learner_circle.as_mut()), seed);
+    }
+    Ok(())
+}
+
+fn print_training_stats(stats: TrainingStats) {
+    println!(
+        "X wins: {}\nO wins: {}\nDraws: {}",
+        stats.cross_wins, stats.circle_wins, stats.draws
+    );
+}
+
+/// Prints how the trained player does against a perfect and a random player.
+/// `circle` plays the O side if the learner was trained as two separate players.
+fn print_strength(
+    cross: &mut dyn Player,
+    mut circle: Option<&mut dyn Player>,
+    seed: u64,
+) {
+    const GAMES: usize = 100;
+    let mut opponents: [(&str, Box<dyn Player>); 2] = [
+        ("perfect", Box::new(PerfectPlayer::new_seeded(seed))),
+        ("random", Box::new(RandomPlayer::new_seeded(seed))),
+    ];
+    for (name, opponent) in &mut opponents {
+        let as_cross = evaluation::evaluate_as_cross(cross, opponent.as_mut(), GAMES);
+        let as_circle = match circle.as_deref_mut() {
+            Some(circle) => {
+                evaluation::evaluate_as_circle(circle, opponent.as_mut(), GAMES)
+            }
+            None => evaluation::evaluate_as_circle(cross, opponent.as_mut(), GAMES),
+        };
+        println!(
+            "Against {} (W/D/L): as X {}, as O {}",
+            name, as_cross, as_circle
+        );
+    }
+}
+
+fn run_curriculum(args: &[String]) -> Result<(), String> {
+    let mut config = CurriculumConfig::default();
+    let mut agent_config = AgentConfig::default();
+    let mut out = None;
+    for (option, value) in option_pairs(args)? {
+        match option {
+            "--rounds" => config.rounds = parse_value(option, value)?,
+            "--games" => config.games_per_round = parse_value(option, value)?,
+            "--eval-games" => config.evaluation_games = parse_value(option, value)?,
+            "--snapshot-every" => config.snapshot_every = parse_value(option, value)?,
+            "--max-snapshots" => config.max_snapshots = parse_value(option, value)?,
+            "--epsilon" => agent_config.epsilon = parse_rate(option, value)?,
+            "--seed" => config.seed = parse_value(option, value)?,
+            "--out" => out = Some(value),
+            _ => return Err(format!("unknown option `{}`", option)),
+        }
+    }
+
+    let mut agent = Agent::new_seeded(agent_config, config.seed);
+    curriculum::run_curriculum(&mut agent, &config, |report| {
+        let results: Vec<String> = report
+            .results
+            .iter()
+            .map(|(opponent, result)| format!("{} {}", opponent, result))
+            .collect();
+        println!(
+            "round {:>3}  stage {}  strength {:>5.1}%  snapshots {}  | {}",
+            report.round + 1,
+            report.stage,
+            report.strength * 100.0,
+            report.snapshots,
+            results.join(", ")
+        );
+    });
+    if let Some(out) = out {
+        agent
+            .save(out)
+            .map_err(|err| format!("failed to save `{}`: {}", out, err))?;
+        println!("Saved the agent to `{}`", out);
+    }
+    Ok(())
+}
+
+fn run_alphazero(args: &[String]) -> Result<(), String> {
+    let mut config = AlphaZeroConfig::default();
+    let mut neural = false;
+    let mut learning_rate = 0.3;
+    let mut hidden_units = 64;
+    let mut optimizer = LearnerParams::default().optimizer;
+    for (option, value) in option_pairs(args)? {
+        match option {
+            "--iterations" => config.iterations = parse_value(option, value)?,
+            "--games" => config.games_per_iteration = parse_value(option, value)?,
+            "--simulations" => config.simulations = parse_value(option, value)?,
+            "--epochs" => config.epochs = parse_value(option, value)?,
+            "--window" => config.window = parse_value(option, value)?,
+            "--model" => {
+                neural = match value {
+                    "neural" => true,
+                    "table" => false,
+                    _ => return Err(format!("invalid model `{}`", value)),
+                }
+            }
+            "--learning-rate" => learning_rate = parse_value(option, value)?,
+            "--hidden" => hidden_units = parse_size(option, value)?,
+            "--optimizer" => optimizer = value.parse()?,
+            "--eval-games" => config.evaluation_games = parse_value(option, value)?,
+            "--seed" => config.seed = parse_value(option, value)?,
+            _ => return Err(format!("unknown option `{}`", option)),
+        }
+    }
+    config.model = if neural {
+        PolicyModel::Neural {
+            hidden_units,
+            optimizer,
+        }
+    } else {
+        PolicyModel::Table { learning_rate }
+    };
+
+    let mut agent = AlphaZeroAgent::new_seeded(&config, config.seed);
+    alphazero::run_alphazero(&mut agent, &config, |report| {
+        println!(
+            "iteration {:>3}  positions {:>5}  strength {:>5.1}%",
+            report.iteration + 1,
+            report.positions,
+            report.strength * 100.0
+        );
+    });
+    print_strength(&mut agent, None, config.seed);
+    Ok(())
+}
+
+fn run_exploit(args: &[String]) -> Result<(), String> {
+    let mut opponent_spec = None;
+    let mut opponent_games = 2000;
+    let mut games = 1000;
+    let mut evaluation_games = 500;
+    let mut prior = 1.0;
+    let mut safe = true;
+    let mut seed = 0;
+    for (option, value) in option_pairs(args)? {
+        match option {
+            "--opponent" => opponent_spec = Some(value),
+            "--opponent-games" => opponent_games = parse_value(option, value)?,
+            "--games" => games = parse_value(option, value)?,
+            "--eval-games" => evaluation_games = parse_value(option, value)?,
+            "--prior" => prior = parse_value(option, value)?,
+            "--moves" => {
+                safe = match value {
+                    "safe" => true,
+                    "any" => false,
+                    _ => return Err(format!("invalid moves `{}`", value)),
+                }
+            }
+            "--seed" => seed = parse_value::<u64>(option, value)?,
+            _ => return Err(format!("unknown option `{}`", option)),
+        }
+    }
+    let (name, mut opponent): (String, Box<dyn Player>) = match opponent_spec {
+        Some(spec) if Path::new(spec).is_file() => {
+            let path = Path::new(spec);
+            (agent_name(path), load_player(path, seed.wrapping_add(1))?)
+        }
+        Some(spec) => (
+            spec.to_string(),
+            player::from_spec(spec, seed.wrapping_add(1))?,
+        ),
+        None => {
+            let config = AgentConfig::default();
+            let mut agent_cross = Agent::new_seeded(config, seed.wrapping_add(1));
+            let mut agent_circle = Agent::new_seeded(config, seed.wrapping_add(2));
+            training::train_self_play(
+                &mut agent_cross,
+                &mut agent_circle,
+                Rewards::default(),
+                opponent_games,
+            );
+            agent_cross.merge(&agent_circle);
+            let name = format!("an agent after {} games", opponent_games);
+            (name, Box::new(agent_cross))
+        }
+    };
+
+    let mut agent = OpponentModelAgent::new_seeded(prior, seed);
+    if !safe {
+        agent = agent.unrestricted();
+    }
+    let result = opponent_model::model_opponent(&mut agent, opponent.as_mut(), games);
+    println!(
+        "Modeled {} on {} boards in {} games (W/D/L): {}",
+        name,
+        agent.modeled_boards_count(),
+        games,
+        result
+    );
+    let mut perfect = PerfectPlayer::new_seeded(seed);
+    let mut scores = Vec::new();
+    for (player_name, player) in [
+        ("best response", &mut agent as &mut dyn Player),
+        ("minimax", &mut perfect),
+    ] {
+        let as_cross =
+            evaluation::evaluate_as_cross(player, opponent.as_mut(), evaluation_games);
+        let as_circle =
+            evaluation::evaluate_as_circle(player, opponent.as_mut(), evaluation_games);
+        let score = (as_cross + as_circle).score();
+        println!(
+            "{:<13} (W/D/L): as X {}, as O {}, score {:.1}%",
+            player_name,
+            as_cross,
+            as_circle,
+            score * 100.0
+        );
+        scores.push(score);
+    }
+    println!(
+        "Gain from modeling: {:+.1} points per 100 games",
+        (scores[0] - scores[1]) * 100.0
+    );
+    Ok(())
+}
+
+fn run_engine(args: &[String]) -> Result<(), String> {
+    let mut agent_path = None;
+    let mut seed = rand::random::<u64>();
+    for (option, value) in option_pairs(args)? {
+        match option {
+            "--agent" => agent_path = Some(value),
+            "--seed" => seed = parse_value(option, value)?,
+            _ => return Err(format!("unknown option `{}`", option)),
+        }
+    }
+    let agent = match agent_path {
+        Some(path) => load_agent(Path::new(path), seed)?,
+        None => SavedAgent::Table(Agent::new_seeded(AgentConfig::default(), seed)),
+    };
+    let mut engine = Engine::new(agent);
+    let stdin = std::io::stdin();
+    engine::run(&mut engine, stdin.lock(), &mut std::io::stdout())
+        .map_err(|err| err.to_string())
+}
+
+fn run_dot(args: &[String]) -> Result<(), String> {
+    let mut options = DotOptions::default();
+    let mut agent_path = None;
+    let mut out = None;
+    for (option, value) in option_pairs(args)? {
+        match option {
+            "--agent" => agent_path = Some(value),
+            "--depth" => options.max_depth = parse_value(option, value)?,
+            "--min-visits" => options.min_visits = parse_value(option, value)?,
+            "--out" => out = Some(value),
+            _ => return Err(format!("unknown option `{}`", option)),
+        }
+    }
+    let path = agent_path.ok_or("missing `--agent`")?;
+    let agent =
+        Agent::load(path).map_err(|err| format!("failed to load `{}`: {}", path, err))?;
+    write_output(out, |writer| {
+        dot::write_policy_tree(&agent, &options, writer)
+    })
+}
+
+fn run_report(args: &[String]) -> Result<(), String> {
+    let mut config = ReportConfig::default();
+    let mut out = None;
+    for (option, value) in option_pairs(args)? {
+        match option {
+            "--games" => config.games = parse_value(option, value)?,
+            "--checkpoints" => config.checkpoints = parse_value(option, value)?,
+            "--eval-games" => config.evaluation_games = parse_value(option, value)?,
+            "--epsilon" => config.agent.epsilon = parse_rate(option, value)?,
+            "--decay" => config.agent.epsilon_decay = parse_rate(option, value)?,
+            "--min-epsilon" => config.agent.min_epsilon = parse_rate(option, value)?,
+            "--seed" => config.seed = parse_value(option, value)?,
+            "--out" => out = Some(value),
+            _ => return Err(format!("unknown option `{}`", option)),
+        }
+    }
+    let report = report::run_training(&config, |checkpoint| {
+        eprintln!(
+            "games {:>7}  not lost vs perfect {:>5.1}%  won vs random {:>5.1}%",
+            checkpoint.games,
+            checkpoint.vs_perfect.non_loss_rate() * 100.0,
+            checkpoint.vs_random.wins as f64 / checkpoint.vs_random.games().max(1) as f64
+                * 100.0
+        );
+    });
+    write_output(out, |writer| report.write_html(writer))?;
+    if let Some(out) = out {
+        println!(
+            "Wrote the report with {} mistakes to `{}`",
+            report.mistakes.len(),
+            out
+        );
+    }
+    Ok(())
+}
+
+fn run_sweep(args: &[String]) -> Result<(), String> {
+    let mut config = SweepConfig::default();
+    let mut out = None;
+    for (option, value) in option_pairs(args)? {
+        match option {
+            "--win" => config.win_rewards = sweep::parse_grid(value)?,
+            "--loss" => config.loss_rewards = sweep::parse_grid(value)?,
+            "--draw" => config.draw_rewards = sweep::parse_grid(value)?,
+            "--epsilon" => config.epsilons = parse_rate_grid(option, value)?,
+            "--decay" => config.epsilon_decays = parse_rate_grid(option, value)?,
+            "--min-epsilon" => config.min_epsilons = parse_rate_grid(option, value)?,
+            "--learner" => config.learner = value.parse()?,
+            "--learning-rate" => config.learning_rates = sweep::parse_grid(value)?,
+            "--lambda" => {
+                let lambdas = parse_rate_grid(option, value)?;
+                config.lambdas = lambdas.into_iter().map(Some).collect();
+            }
+            "--trace" => config.trace_kind = value.parse()?,
+            "--games" => config.training_games = parse_value(option, value)?,
+            "--eval-games" => config.evaluation_games = parse_value(option, value)?,
+            "--seed" => config.seed = parse_value(option, value)?,
+            "--out" => out = Some(value),
+            _ => return Err(format!("unknown option `{}`", option)),
+        }
+    }
+
+    let results = sweep::run_sweep(&config, |index, total, result| {
+        eprintln!(
+            "[{}/{}] rewards ({}, {}, {}), epsilon {}, decay {}, min {}, lr {}, \
+             lambda {}: X {}, O {}",
+            index + 1,
+            total,
+            result.rewards.win,
+            result.rewards.loss,
+            result.rewards.draw,
+            result.config.epsilon,
+            result.config.epsilon_decay,
+            result.config.min_epsilon,
+            result.params.learning_rate,
+            sweep::lambda_label(result.params.lambda),
+            result.as_cross,
+            result.as_circle
+        )
+    });
+    write_output(out, |out| sweep::write_table(&results, out))
+}
+
+fn run_tournament(args: &[String]) -> Result<(), String> {
+    let mut agent_dirs = Vec::new();
+    let mut player_specs = Vec::new();
+    let mut games = 10;
+    let mut seed: u64 = 0;
+    let mut out = None;
+    for (option, value) in option_pairs(args)? {
+        match option {
+            "--agents" => agent_dirs.push(value),
+            "--player" => player_specs.push(value),
+            "--games" => games = parse_value(option, value)?,
+            "--seed" => seed = parse_value(option, value)?,
+            "--out" => out = Some(value),
+            _ => return Err(format!("unknown option `{}`", option)),
+        }
+    }
+
+    let mut entrants = Vec::new();
+    for dir in agent_dirs {
+        let mut paths: Vec<_> = std::fs::read_dir(dir)
+            .map_err(|err| format!("failed to read `{}`: {}", dir, err))?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "agent"))
+            .collect();
+        paths.sort();
+        for path in paths {
+            let player = load_player(&path, seed.wrapping_add(entrants.len() as u64))?;
+            entrants.push(Entrant::new(agent_name(&path), player));
+        }
+    }
+    for spec in player_specs {
+        let player = player::from_spec(spec, seed.wrapping_add(entrants.len() as u64))?;
+        entrants.push(Entrant::new(spec, player));
+    }
+    if entrants.len() < 2 {
+        return Err("a tournament needs at least two entrants".to_string());
+    }
+
+    let result = tournament::run_round_robin(&mut entrants, games);
+    write_output(out, |out| result.write_report(out))
+}
+
+#[cfg(feature = "tui")]
+fn run_tui(args: &[String]) -> Result<(), String> {
+    use std::time::Duration;
+    use tic_tac_toe_learning_ai::tui::{self, App};
+
+    let mut cross = "human";
+    let mut circle = "medium";
+    let mut delay = 500;
+    let mut seed = rand::random::<u64>();
+    for (option, value) in option_pairs(args)? {
+        match option {
+            "--x" => cross = value,
+            "--o" => circle = value,
+            "--delay" => delay = parse_value(option, value)?,
+            "--seed" => seed = parse_value(option, value)?,
+            _ => return Err(format!("unknown option `{}`", option)),
+        }
+    }
+    let app = App::new(
+        load_seat(cross, seed)?,
+        load_seat(circle, seed.wrapping_add(1))?,
+    )
+    .with_delay(Duration::from_millis(delay));
+    tui::run(app).map_err(|err| err.to_string())
+}
+
+/// Builds a seat of the terminal UI whose value overlay shows the biases of a
+/// saved agent, the move values of a neural agent or the minimax scores behind a
+/// scripted player.
+#[cfg(feature = "tui")]
+fn load_seat(
+    side: &str,
+    seed: u64,
+) -> Result<tic_tac_toe_learning_ai::tui::Seat, String> {
+    use tic_tac_toe_learning_ai::minimax::Minimax;
+    use tic_tac_toe_learning_ai::tui::Seat;
+
+    let path = Path::new(side);
+    if side == "human" {
+        return Ok(Seat::human());
+    }
+    if !path.is_file() {
+        let mut minimax = Minimax::new();
+        let seat = Seat::computer(side, player::from_spec(side, seed)?);
+        return Ok(seat.with_values(move |board| {
+            minimax
+                .move_scores(board)
+                .into_iter()
+                .map(|(pos, score)| (pos, score as f64))
+                .collect()
+        }));
+    }
+    let name = agent_name(path);
+    match load_agent(path, seed)? {
+        SavedAgent::Table(agent) => {
+            let values = agent.clone();
+            Ok(
+                Seat::computer(name, Box::new(agent)).with_values(move |board| {
+                    let actions =
+                        values.get_actions_from_board(board).unwrap_or_default();
+                    actions
+                        .into_iter()
+                        .map(|action| (action.field_pos, action.bias as f64))
+                        .collect()
+                }),
+            )
+        }
+        SavedAgent::Neural(agent) => {
+            let values = agent.clone();
+            Ok(Seat::computer(name, Box::new(agent))
+                .with_values(move |board| values.move_values(board)))
+        }
+    }
+}
+
+fn load_agent(path: &Path, seed: u64) -> Result<SavedAgent, String> {
+    let mut agent = SavedAgent::load(path)
+        .map_err(|err| format!("failed to load `{}`: {}", path.display(), err))?;
+    agent.reseed(seed);
+    Ok(agent)
+}
+
+/// Loads a saved table agent or neural agent, telling them apart by their header.
+fn load_player(path: &Path, seed: u64) -> Result<Box<dyn Player>, String> {
+    load_agent(path, seed).map(SavedAgent::into_player)
+}
+
+fn agent_name(path: &Path) -> String {
+    path.file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.display().to_string())
+}
+
+fn watch_self_play() {
     println!(
         "boards memorized: {}",
         Agent::new_blank(0.0).memorized_boards_count()
@@ -15,37 +1059,15 @@ fn main() {
 
     let mut agent_cross = Agent::new_blank(0.6);
     let mut agent_circle = Agent::new_blank(0.6);
-    let mut cross_wins = 0;
-    let mut circle_wins = 0;
-    let mut draws = 0;
-    for _ in 0..100_000 {
-        game.run_new(|event, board_state| match event {
-            GameEvent::CrossWon => {
-                agent_cross.give_feedback(6);
-                agent_circle.give_feedback(-3);
-                cross_wins += 1;
-            }
-            GameEvent::CircleWon => {
-                agent_cross.give_feedback(-3);
-                agent_circle.give_feedback(6);
-                circle_wins += 1;
-            }
-            GameEvent::Draw => {
-                agent_cross.give_feedback(1);
-                agent_circle.give_feedback(1);
-                draws += 1;
-            }
-            GameEvent::CrossTurn => {
-                let position = agent_cross.play_greedy_exploration(board_state);
-                board_state.play_move_at(position.unwrap());
-            }
-            GameEvent::CircleTurn => {
-                let position = agent_circle.play_greedy_exploration(board_state);
-                board_state.play_move_at(position.unwrap());
-            }
-            GameEvent::InvalidBoard => unreachable!(),
-        })
-    }
+    let stats = training::train_self_play(
+        &mut agent_cross,
+        &mut agent_circle,
+        Rewards::default(),
+        100_000,
+    );
+    let mut cross_wins = stats.cross_wins;
+    let mut circle_wins = stats.circle_wins;
+    let mut draws = stats.draws;
 
     loop {
         game.run_new(|event, board_state| {
@@ -84,43 +1106,22 @@ fn main() {
                     );
                 }
                 GameEvent::CrossTurn => {
-                    let position = agent_cross.play_greedy_exploration(board_state);
-                    println!("Cross plays: {:?}", position);
-                    if let Some(actions) = agent_cross.get_actions_from_board(board_state)
-                    {
-                        for action in actions {
-                            println!(
-                                "POS: ({}, {}), BIAS: {}",
-                                action.field_pos.x, action.field_pos.y, action.bias
-                            )
-                        }
-                        println!(
-                            "Memorized Boards: {}, epsilon: {}",
-                            agent_cross.memorized_boards_count(),
-                            agent_cross.epsilon()
-                        );
-                    }
-                    board_state.play_move_at(position.unwrap());
+                    let decision = agent_cross.decide(board_state).unwrap();
+                    println!("{}", decision);
+                    println!(
+                        "Memorized Boards: {}",
+                        agent_cross.memorized_boards_count()
+                    );
+                    board_state.play_move_at(decision.chosen);
                 }
                 GameEvent::CircleTurn => {
-                    let position = agent_circle.play_greedy_exploration(board_state);
-                    println!("Circle plays: {:?}", position);
-                    if let Some(actions) =
-                        agent_circle.get_actions_from_board(board_state)
-                    {
-                        for action in actions {
-                            println!(
-                                "POS: ({}, {}), BIAS: {}",
-                                action.field_pos.x, action.field_pos.y, action.bias
-                            )
-                        }
-                        println!(
-                            "Memorized Boards: {}, epsilon: {}",
-                            agent_circle.memorized_boards_count(),
-                            agent_cross.epsilon()
-                        );
-                    }
-                    board_state.play_move_at(position.unwrap());
+                    let decision = agent_circle.decide(board_state).unwrap();
+                    println!("{}", decision);
+                    println!(
+                        "Memorized Boards: {}",
+                        agent_circle.memorized_boards_count()
+                    );
+                    board_state.play_move_at(decision.chosen);
                 }
                 GameEvent::InvalidBoard => unreachable!(),
             }
@@ -0,0 +1,147 @@
+use crate::board::{Board, FieldPosition, FieldState};
+use crate::player::Player;
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+
+/// Monte Carlo tree search player using UCT selection and random playouts.
+///
+/// `iterations` is the search budget per move. The tree is rebuilt for every
+/// move.
+pub struct MctsPlayer {
+    iterations: usize,
+    exploration: f64,
+    rng: StdRng,
+}
+
+struct Node {
+    board: Board,
+    parent: Option<usize>,
+    field_pos: Option<FieldPosition>,
+    children: Vec<usize>,
+    untried: Vec<FieldPosition>,
+    visits: u32,
+    /// Sum of results from the view of the player who moved into this node.
+    score: f64,
+}
+
+impl Node {
+    fn new(
+        board: Board,
+        parent: Option<usize>,
+        field_pos: Option<FieldPosition>,
+    ) -> Self {
+        let untried = if board.is_finished() {
+            Vec::new()
+        } else {
+            board.get_empty_fields_pos()
+        };
+        Self {
+            board,
+            parent,
+            field_pos,
+            children: Vec::new(),
+            untried,
+            visits: 0,
+            score: 0.0,
+        }
+    }
+}
+
+impl MctsPlayer {
+    pub fn new(iterations: usize) -> Self {
+        Self::with_rng(iterations, StdRng::from_entropy())
+    }
+
+    pub fn new_seeded(iterations: usize, seed: u64) -> Self {
+        Self::with_rng(iterations, StdRng::seed_from_u64(seed))
+    }
+
+    fn with_rng(iterations: usize, rng: StdRng) -> Self {
+        Self {
+            iterations,
+            exploration: std::f64::consts::SQRT_2,
+            rng,
+        }
+    }
+
+    pub fn iterations(&self) -> usize {
+        self.iterations
+    }
+
+    /// Searches the board and returns how often each legal move was visited.
+    pub fn visit_counts(&mut self, board: &Board) -> Vec<(FieldPosition, u32)> {
+        if board.is_finished() {
+            return Vec::new();
+        }
+        let mut tree = vec![Node::new(board.clone(), None, None)];
+        for _ in 0..self.iterations.max(1) {
+            // Selection
+            let mut node = 0;
+            while tree[node].untried.is_empty() && !tree[node].children.is_empty() {
+                node = self.select_child(&tree, node);
+            }
+            // Expansion
+            if !tree[node].untried.is_empty() {
+                let i = self.rng.gen_range(0..tree[node].untried.len());
+                let pos = tree[node].untried.swap_remove(i);
+                let mut board = tree[node].board.clone();
+                board.play_move_at(pos);
+                tree.push(Node::new(board, Some(node), Some(pos)));
+                let child = tree.len() - 1;
+                tree[node].children.push(child);
+                node = child;
+            }
+            // Simulation
+            let winner = self.playout(tree[node].board.clone());
+            // Backpropagation
+            let mut current = Some(node);
+            while let Some(i) = current {
+                let mover = tree[i].board.next_player().opponent();
+                tree[i].visits += 1;
+                tree[i].score += if winner == FieldState::Empty {
+                    0.5
+                } else if winner == mover {
+                    1.0
+                } else {
+                    0.0
+                };
+                current = tree[i].parent;
+            }
+        }
+        tree[0]
+            .children
+            .iter()
+            .map(|&child| (tree[child].field_pos.unwrap(), tree[child].visits))
+            .collect()
+    }
+
+    fn select_child(&self, tree: &[Node], node: usize) -> usize {
+        let parent_visits = (tree[node].visits as f64).ln();
+        let uct = |child: usize| {
+            let child = &tree[child];
+            let visits = child.visits as f64;
+            child.score / visits + self.exploration * (parent_visits / visits).sqrt()
+        };
+        *tree[node]
+            .children
+            .iter()
+            .max_by(|&&a, &&b| uct(a).total_cmp(&uct(b)))
+            .unwrap()
+    }
+
+    fn playout(&mut self, mut board: Board) -> FieldState {
+        while !board.is_finished() {
+            let pos = *board.get_empty_fields_pos().choose(&mut self.rng).unwrap();
+            board.play_move_at(pos);
+        }
+        board.find_winner()
+    }
+}
+
+impl Player for MctsPlayer {
+    fn choose_move(&mut self, board: &Board) -> Option<FieldPosition> {
+        self.visit_counts(board)
+            .into_iter()
+            .max_by_key(|&(_, visits)| visits)
+            .map(|(pos, _)| pos)
+    }
+}
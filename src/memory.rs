@@ -0,0 +1,209 @@
+use crate::board::{Board, FieldPosition, FieldState};
+use hashbrown::HashMap;
+
+/// Boards mapped to values, where all rotations and reflections of a board share
+/// the entry of whichever version was saved first.
+#[derive(Clone)]
+pub struct BoardMemory<V> {
+    boards: HashMap<Board, V>,
+}
+
+impl<V> BoardMemory<V> {
+    pub fn new() -> Self {
+        Self {
+            boards: HashMap::new(),
+        }
+    }
+
+    /// Returns the entry of exactly this saved board.
+    pub fn get(&self, saved_board: &Board) -> Option<&V> {
+        self.boards.get(saved_board)
+    }
+
+    pub fn get_mut(&mut self, saved_board: &Board) -> Option<&mut V> {
+        self.boards.get_mut(saved_board)
+    }
+
+    /// Returns the entry of `board` or of any of its symmetric versions.
+    pub fn lookup(&self, board: &Board) -> Option<&V> {
+        self.find(board)
+            .and_then(|(saved_board, _)| self.boards.get(&saved_board))
+    }
+
+    pub fn insert(&mut self, board: Board, value: V) {
+        self.boards.insert(board, value);
+    }
+
+    /// Returns the saved version of `board`, saving `board` itself with the value
+    /// from `default` if no symmetric version is known yet.
+    pub fn find_or_insert_with<F>(
+        &mut self,
+        board: &Board,
+        default: F,
+    ) -> (Board, BoardTransformation)
+    where
+        F: FnOnce() -> V,
+    {
+        match self.find(board) {
+            Some(saved) => saved,
+            None => {
+                self.boards.insert(board.clone(), default());
+                (board.clone(), BoardTransformation::None)
+            }
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.boards.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.boards.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&Board, &V)> {
+        self.boards.iter()
+    }
+
+    /// Finds the saved board which is `board` or one of its rotations or
+    /// reflections, together with the transformation leading to it.
+    pub fn find(&self, board: &Board) -> Option<(Board, BoardTransformation)> {
+        if self.boards.contains_key(board) {
+            return Some((board.clone(), BoardTransformation::None));
+        }
+        // Also check if identical transformed board is saved
+        // Rotated 90 degrees clockwise
+        let rotated = board.get_rotated_90_clockwise();
+        if self.boards.contains_key(&rotated) {
+            return Some((rotated, BoardTransformation::Rotated90CW));
+        }
+        // Rotated 180 degrees
+        let rotated = rotated.get_rotated_90_clockwise();
+        if self.boards.contains_key(&rotated) {
+            return Some((rotated, BoardTransformation::Rotated180));
+        }
+        // Rotated 270 clockwise
+        let rotated = rotated.get_rotated_90_clockwise();
+        if self.boards.contains_key(&rotated) {
+            return Some((rotated, BoardTransformation::Rotated90CCW));
+        }
+
+        let flipped = board.get_flipped_horizontally();
+        if self.boards.contains_key(&flipped) {
+            return Some((flipped, BoardTransformation::FlippedHorizontally));
+        }
+        let flipped = board.get_flipped_vertically();
+        if self.boards.contains_key(&flipped) {
+            return Some((flipped, BoardTransformation::FlippedVertically));
+        }
+        let flipped = board.get_flipped_diagonally_southwest_northeast();
+        if self.boards.contains_key(&flipped) {
+            return Some((flipped, BoardTransformation::FlippedDiagonallySWNE));
+        }
+        let flipped = board.get_flipped_diagonally_northwest_southeast();
+        if self.boards.contains_key(&flipped) {
+            return Some((flipped, BoardTransformation::FlippedDiagonallyNWSE));
+        }
+        None
+    }
+}
+
+impl<V> Default for BoardMemory<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BoardTransformation {
+    None,
+    Rotated90CW,
+    Rotated180,
+    Rotated90CCW,
+    FlippedHorizontally,
+    FlippedVertically,
+    FlippedDiagonallySWNE,
+    FlippedDiagonallyNWSE,
+}
+
+impl std::fmt::Display for BoardTransformation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BoardTransformation::None => write!(f, "as is"),
+            BoardTransformation::Rotated90CW => write!(f, "rotated 90° clockwise"),
+            BoardTransformation::Rotated180 => write!(f, "rotated 180°"),
+            BoardTransformation::Rotated90CCW => {
+                write!(f, "rotated 90° counterclockwise")
+            }
+            BoardTransformation::FlippedHorizontally => write!(f, "flipped horizontally"),
+            BoardTransformation::FlippedVertically => write!(f, "flipped vertically"),
+            BoardTransformation::FlippedDiagonallySWNE => {
+                write!(f, "flipped along the southwest-northeast diagonal")
+            }
+            BoardTransformation::FlippedDiagonallyNWSE => {
+                write!(f, "flipped along the northwest-southeast diagonal")
+            }
+        }
+    }
+}
+
+impl BoardTransformation {
+    fn inverse(&self) -> Self {
+        match self {
+            BoardTransformation::Rotated90CW => BoardTransformation::Rotated90CCW,
+            BoardTransformation::Rotated90CCW => BoardTransformation::Rotated90CW,
+            BoardTransformation::None => BoardTransformation::None,
+            BoardTransformation::Rotated180 => BoardTransformation::Rotated180,
+            BoardTransformation::FlippedHorizontally => {
+                BoardTransformation::FlippedHorizontally
+            }
+            BoardTransformation::FlippedVertically => {
+                BoardTransformation::FlippedVertically
+            }
+            BoardTransformation::FlippedDiagonallySWNE => {
+                BoardTransformation::FlippedDiagonallySWNE
+            }
+            BoardTransformation::FlippedDiagonallyNWSE => {
+                BoardTransformation::FlippedDiagonallyNWSE
+            }
+        }
+    }
+
+    /// Maps a position on the original board to the saved board.
+    pub fn pos_to_saved(&self, pos: FieldPosition) -> FieldPosition {
+        self.inverse().pos_to_original(pos)
+    }
+
+    /// Maps a position on the saved board back to the original board.
+    pub fn pos_to_original(&self, pos: FieldPosition) -> FieldPosition {
+        let mut board = Board::EMPTY;
+        board.0[pos.y][pos.x] = FieldState::X;
+        let transformed_board = match self {
+            BoardTransformation::None => return pos,
+            BoardTransformation::Rotated90CW => board
+                .get_rotated_90_clockwise()
+                .get_rotated_90_clockwise()
+                .get_rotated_90_clockwise(),
+            BoardTransformation::Rotated180 => {
+                board.get_rotated_90_clockwise().get_rotated_90_clockwise()
+            }
+            BoardTransformation::Rotated90CCW => board.get_rotated_90_clockwise(),
+            BoardTransformation::FlippedHorizontally => board.get_flipped_horizontally(),
+            BoardTransformation::FlippedVertically => board.get_flipped_vertically(),
+            BoardTransformation::FlippedDiagonallySWNE => {
+                board.get_flipped_diagonally_southwest_northeast()
+            }
+            BoardTransformation::FlippedDiagonallyNWSE => {
+                board.get_flipped_diagonally_northwest_southeast()
+            }
+        };
+        for y in 0..3 {
+            for x in 0..3 {
+                if transformed_board.0[y][x] == FieldState::X {
+                    return FieldPosition::new(x, y);
+                }
+            }
+        }
+        unreachable!()
+    }
+}
@@ -0,0 +1,363 @@
+use crate::board::{Board, FieldPosition, FieldState};
+use crate::player::Player;
+use hashbrown::HashMap;
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+
+/// Exhaustive minimax solver with a memoized transposition table.
+///
+/// Scores are given from the perspective of the player to move: positive values
+/// are wins, negative values are losses and `0` is a draw. Faster wins score
+/// higher than slower ones.
+#[derive(Default)]
+pub struct Minimax {
+    memo: HashMap<Board, i32>,
+    limited_memo: HashMap<(Board, usize), i32>,
+}
+
+impl Minimax {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn evaluate(&mut self, board: &Board) -> i32 {
+        if let Some(&score) = self.memo.get(board) {
+            return score;
+        }
+        let empty_field_count = board.field_state_count(FieldState::Empty) as i32;
+        let score = if board.find_winner() != FieldState::Empty {
+            // The previous move won the game
+            -(empty_field_count + 1)
+        } else if empty_field_count == 0 {
+            0
+        } else {
+            board
+                .get_empty_fields_pos()
+                .into_iter()
+                .map(|pos| {
+                    let mut next = board.clone();
+                    next.play_move_at(pos);
+                    -self.evaluate(&next)
+                })
+                .max()
+                .unwrap()
+        };
+        self.memo.insert(board.clone(), score);
+        score
+    }
+
+    /// Like [`Minimax::evaluate`] but only looks `depth` moves ahead.
+    /// Positions which are not decided within the horizon score `0`.
+    pub fn evaluate_to_depth(&mut self, board: &Board, depth: usize) -> i32 {
+        let empty_field_count = board.field_state_count(FieldState::Empty);
+        if depth >= empty_field_count || board.is_finished() {
+            return self.evaluate(board);
+        }
+        if depth == 0 {
+            return 0;
+        }
+        if let Some(&score) = self.limited_memo.get(&(board.clone(), depth)) {
+            return score;
+        }
+        let score = board
+            .get_empty_fields_pos()
+            .into_iter()
+            .map(|pos| {
+                let mut next = board.clone();
+                next.play_move_at(pos);
+                -self.evaluate_to_depth(&next, depth - 1)
+            })
+            .max()
+            .unwrap();
+        self.limited_memo.insert((board.clone(), depth), score);
+        score
+    }
+
+    /// Returns every legal move together with its score for the player to move.
+    pub fn move_scores(&mut self, board: &Board) -> Vec<(FieldPosition, i32)> {
+        self.move_scores_to_depth(board, 9)
+    }
+
+    /// Scores every legal move looking `depth` moves ahead, the move itself included.
+    pub fn move_scores_to_depth(
+        &mut self,
+        board: &Board,
+        depth: usize,
+    ) -> Vec<(FieldPosition, i32)> {
+        if board.is_finished() {
+            return Vec::new();
+        }
+        board
+            .get_empty_fields_pos()
+            .into_iter()
+            .map(|pos| {
+                let mut next = board.clone();
+                next.play_move_at(pos);
+                (pos, -self.evaluate_to_depth(&next, depth.saturating_sub(1)))
+            })
+            .collect()
+    }
+
+    /// Returns all moves which keep the best achievable result.
+    pub fn best_moves(&mut self, board: &Board) -> Vec<FieldPosition> {
+        self.best_moves_to_depth(board, 9)
+    }
+
+    /// Returns all moves which look best when searching `depth` moves ahead.
+    pub fn best_moves_to_depth(
+        &mut self,
+        board: &Board,
+        depth: usize,
+    ) -> Vec<FieldPosition> {
+        let scores = self.move_scores_to_depth(board, depth);
+        let best = match scores.iter().map(|&(_, score)| score).max() {
+            Some(best) => best,
+            None => return Vec::new(),
+        };
+        scores
+            .into_iter()
+            .filter(|&(_, score)| score == best)
+            .map(|(pos, _)| pos)
+            .collect()
+    }
+}
+
+/// Player that never loses, picking randomly between equally good moves.
+pub struct PerfectPlayer {
+    minimax: Minimax,
+    rng: StdRng,
+}
+
+impl PerfectPlayer {
+    pub fn new() -> Self {
+        Self::with_rng(StdRng::from_entropy())
+    }
+
+    pub fn new_seeded(seed: u64) -> Self {
+        Self::with_rng(StdRng::seed_from_u64(seed))
+    }
+
+    fn with_rng(rng: StdRng) -> Self {
+        Self {
+            minimax: Minimax::new(),
+            rng,
+        }
+    }
+}
+
+impl Default for PerfectPlayer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Player for PerfectPlayer {
+    fn choose_move(&mut self, board: &Board) -> Option<FieldPosition> {
+        self.minimax
+            .best_moves(board)
+            .choose(&mut self.rng)
+            .copied()
+    }
+}
+
+/// Minimax player which only searches `depth` moves ahead and picks randomly
+/// between moves that look equally good within that horizon.
+///
+/// With a blunder probability set, the player occasionally picks one of the moves
+/// it considers worse instead, which makes even a full depth search beatable.
+pub struct MinimaxPlayer {
+    minimax: Minimax,
+    depth: usize,
+    blunder_probability: f64,
+    rng: StdRng,
+}
+
+impl MinimaxPlayer {
+    pub fn new(depth: usize) -> Self {
+        Self::with_rng(depth, StdRng::from_entropy())
+    }
+
+    pub fn new_seeded(depth: usize, seed: u64) -> Self {
+        Self::with_rng(depth, StdRng::seed_from_u64(seed))
+    }
+
+    fn with_rng(depth: usize, rng: StdRng) -> Self {
+        Self {
+            minimax: Minimax::new(),
+            depth,
+            blunder_probability: 0.0,
+            rng,
+        }
+    }
+
+    /// Makes the player blunder with the given probability on every move.
+    pub fn with_blunders(mut self, probability: f64) -> Self {
+        self.blunder_probability = probability.clamp(0.0, 1.0);
+        self
+    }
+
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    pub fn blunder_probability(&self) -> f64 {
+        self.blunder_probability
+    }
+}
+
+impl Player for MinimaxPlayer {
+    fn choose_move(&mut self, board: &Board) -> Option<FieldPosition> {
+        let scores = self.minimax.move_scores_to_depth(board, self.depth);
+        let best = scores.iter().map(|&(_, score)| score).max()?;
+        let (best_moves, worse_moves): (Vec<_>, Vec<_>) =
+            scores.into_iter().partition(|&(_, score)| score == best);
+        let candidates =
+            if !worse_moves.is_empty() && self.rng.gen_bool(self.blunder_probability) {
+                worse_moves
+            } else {
+                best_moves
+            };
+        candidates.choose(&mut self.rng).map(|&(pos, _)| pos)
+    }
+}
+
+/// Preset strengths for scripted opponents.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Difficulty {
+    /// Only sees its own immediate wins and blunders often.
+    Easy,
+    /// Also sees the opponent's immediate wins.
+    Medium,
+    /// Searches the whole game but blunders now and then.
+    Hard,
+    /// Never loses.
+    Perfect,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 4] = [
+        Difficulty::Easy,
+        Difficulty::Medium,
+        Difficulty::Hard,
+        Difficulty::Perfect,
+    ];
+
+    pub fn depth(&self) -> usize {
+        match self {
+            Difficulty::Easy => 1,
+            Difficulty::Medium => 2,
+            Difficulty::Hard | Difficulty::Perfect => 9,
+        }
+    }
+
+    pub fn blunder_probability(&self) -> f64 {
+        match self {
+            Difficulty::Easy => 0.3,
+            Difficulty::Medium => 0.1,
+            Difficulty::Hard => 0.1,
+            Difficulty::Perfect => 0.0,
+        }
+    }
+
+    pub fn player(&self) -> MinimaxPlayer {
+        MinimaxPlayer::new(self.depth()).with_blunders(self.blunder_probability())
+    }
+
+    pub fn player_seeded(&self, seed: u64) -> MinimaxPlayer {
+        MinimaxPlayer::new_seeded(self.depth(), seed)
+            .with_blunders(self.blunder_probability())
+    }
+}
+
+impl std::str::FromStr for Difficulty {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "easy" => Ok(Difficulty::Easy),
+            "medium" => Ok(Difficulty::Medium),
+            "hard" => Ok(Difficulty::Hard),
+            "perfect" => Ok(Difficulty::Perfect),
+            _ => Err(format!(
+                "unknown difficulty `{}`, expected easy, medium, hard or perfect",
+                s
+            )),
+        }
+    }
+}
+
+impl std::fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Difficulty::Easy => write!(f, "easy"),
+            Difficulty::Medium => write!(f, "medium"),
+            Difficulty::Hard => write!(f, "hard"),
+            Difficulty::Perfect => write!(f, "perfect"),
+        }
+    }
+}
+
+#[test]
+fn minimax_test() {
+    let mut minimax = Minimax::new();
+    // Perfect play from the empty board is a draw
+    assert_eq!(minimax.evaluate(&Board::EMPTY), 0);
+
+    // X to move and win in the top row
+    let board = Board([
+        [FieldState::X, FieldState::X, FieldState::Empty],
+        [FieldState::O, FieldState::O, FieldState::Empty],
+        [FieldState::Empty, FieldState::Empty, FieldState::Empty],
+    ]);
+    assert_eq!(minimax.best_moves(&board), vec![FieldPosition::new(2, 0)]);
+    assert!(minimax.evaluate(&board) > 0);
+
+    // One move of look-ahead does not see that O threatens to win
+    let board = Board([
+        [FieldState::X, FieldState::Empty, FieldState::Empty],
+        [FieldState::O, FieldState::O, FieldState::Empty],
+        [FieldState::X, FieldState::Empty, FieldState::Empty],
+    ]);
+    assert_eq!(minimax.best_moves(&board), vec![FieldPosition::new(2, 1)]);
+    assert_eq!(minimax.best_moves_to_depth(&board, 1).len(), 5);
+}
+
+#[test]
+fn blunder_test() {
+    // X wins at the top right, every other move is worse
+    let board = Board([
+        [FieldState::X, FieldState::X, FieldState::Empty],
+        [FieldState::O, FieldState::O, FieldState::Empty],
+        [FieldState::Empty, FieldState::Empty, FieldState::Empty],
+    ]);
+    let win = FieldPosition::new(2, 0);
+    let mut careful = MinimaxPlayer::new_seeded(9, 0).with_blunders(0.0);
+    let mut careless = MinimaxPlayer::new_seeded(9, 0).with_blunders(1.0);
+    for _ in 0..20 {
+        assert_eq!(careful.choose_move(&board), Some(win));
+        assert_ne!(careless.choose_move(&board), Some(win));
+    }
+    assert_eq!(
+        MinimaxPlayer::new(9)
+            .with_blunders(1.5)
+            .blunder_probability(),
+        1.0
+    );
+
+    for difficulty in Difficulty::ALL {
+        assert_eq!(difficulty.to_string().parse(), Ok(difficulty));
+        let player = difficulty.player_seeded(0);
+        assert_eq!(player.depth(), difficulty.depth());
+        assert_eq!(
+            player.blunder_probability(),
+            difficulty.blunder_probability()
+        );
+    }
+    assert_eq!(Difficulty::Perfect.blunder_probability(), 0.0);
+    assert!(Difficulty::Easy.depth() < Difficulty::Hard.depth());
+    assert!("impossible".parse::<Difficulty>().is_err());
+
+    assert!(crate::player::from_spec("minimax:3:0.2", 0).is_ok());
+    assert!(crate::player::from_spec("minimax:-3", 0).is_err());
+    assert!(crate::player::from_spec("minimax:9:1.5", 0).is_err());
+    assert!(crate::player::from_spec("mcts:1.7", 0).is_err());
+}
@@ -0,0 +1,370 @@
+use rand::Rng;
+use std::io::{self, BufRead, Write};
+
+/// How the weights of an [`Mlp`] follow their gradients.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Optimizer {
+    /// Plain stochastic gradient descent.
+    Sgd { learning_rate: f64 },
+    /// Adam with the usual decay rates of 0.9 and 0.999.
+    Adam { learning_rate: f64 },
+}
+
+impl Optimizer {
+    pub fn learning_rate(&self) -> f64 {
+        match *self {
+            Optimizer::Sgd { learning_rate } | Optimizer::Adam { learning_rate } => {
+                learning_rate
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for Optimizer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Optimizer::Sgd { learning_rate } => write!(f, "sgd {}", learning_rate),
+            Optimizer::Adam { learning_rate } => write!(f, "adam {}", learning_rate),
+        }
+    }
+}
+
+/// Parses `sgd` or `adam`, optionally followed by `:<learning rate>`.
+impl std::str::FromStr for Optimizer {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, learning_rate) = match s.split_once(':') {
+            Some((name, rate)) => {
+                let rate = rate
+                    .parse()
+                    .map_err(|_| format!("invalid learning rate in `{}`", s))?;
+                (name, Some(rate))
+            }
+            None => (s, None),
+        };
+        match name {
+            "sgd" => Ok(Optimizer::Sgd {
+                learning_rate: learning_rate.unwrap_or(0.01),
+            }),
+            "adam" => Ok(Optimizer::Adam {
+                learning_rate: learning_rate.unwrap_or(0.001),
+            }),
+            _ => Err(format!("unknown optimizer `{}`, expected sgd or adam", s)),
+        }
+    }
+}
+
+const ADAM_BETA1: f64 = 0.9;
+const ADAM_BETA2: f64 = 0.999;
+const ADAM_EPSILON: f64 = 1e-8;
+
+/// Small fully connected network with tanh hidden layers and a linear output.
+#[derive(Debug, Clone)]
+pub struct Mlp {
+    layers: Vec<Layer>,
+    optimizer: Optimizer,
+    /// Number of updates so far, for Adam's bias correction.
+    steps: i32,
+}
+
+#[derive(Debug, Clone)]
+struct Layer {
+    inputs: usize,
+    outputs: usize,
+    /// Row-major, one row of `inputs` weights per output.
+    weights: Vec<f64>,
+    biases: Vec<f64>,
+    /// Adam's first and second moments of every weight followed by every bias.
+    moments: Vec<(f64, f64)>,
+}
+
+impl Layer {
+    fn new<R: Rng>(inputs: usize, outputs: usize, rng: &mut R) -> Self {
+        // Xavier initialization keeps the tanh units out of saturation
+        let limit = (6.0 / (inputs + outputs) as f64).sqrt();
+        Self {
+            inputs,
+            outputs,
+            weights: (0..inputs * outputs)
+                .map(|_| rng.gen_range(-limit..limit))
+                .collect(),
+            biases: vec![0.0; outputs],
+            moments: vec![(0.0, 0.0); (inputs + 1) * outputs],
+        }
+    }
+
+    fn forward(&self, input: &[f64]) -> Vec<f64> {
+        self.weights
+            .chunks(self.inputs)
+            .zip(&self.biases)
+            .map(|(row, bias)| {
+                bias + row.iter().zip(input).map(|(w, x)| w * x).sum::<f64>()
+            })
+            .collect()
+    }
+}
+
+impl Mlp {
+    /// Creates a network with the given layer sizes, from the input size to the
+    /// output size, and randomly initialized weights.
+    ///
+    /// # Panics
+    ///
+    /// Panics with fewer than two sizes or with a size of zero.
+    pub fn new<R: Rng>(sizes: &[usize], optimizer: Optimizer, rng: &mut R) -> Self {
+        assert!(
+            sizes.len() >= 2,
+            "a network needs an input and an output size"
+        );
+        assert!(!sizes.contains(&0), "layers need at least one unit");
+        Self {
+            layers: sizes
+                .windows(2)
+                .map(|pair| Layer::new(pair[0], pair[1], rng))
+                .collect(),
+            optimizer,
+            steps: 0,
+        }
+    }
+
+    /// Layer sizes from the input to the output.
+    pub fn sizes(&self) -> Vec<usize> {
+        let mut sizes = vec![self.layers[0].inputs];
+        sizes.extend(self.layers.iter().map(|layer| layer.outputs));
+        sizes
+    }
+
+    pub fn optimizer(&self) -> Optimizer {
+        self.optimizer
+    }
+
+    pub fn forward(&self, input: &[f64]) -> Vec<f64> {
+        self.activations(input).pop().unwrap()
+    }
+
+    /// Outputs of every layer, starting with the input itself.
+    fn activations(&self, input: &[f64]) -> Vec<Vec<f64>> {
+        let mut activations = vec![input.to_vec()];
+        for (i, layer) in self.layers.iter().enumerate() {
+            let mut output = layer.forward(activations.last().unwrap());
+            if i + 1 < self.layers.len() {
+                output.iter_mut().for_each(|value| *value = value.tanh());
+            }
+            activations.push(output);
+        }
+        activations
+    }
+
+    /// Takes one optimizer step moving a single output towards `target` and
+    /// returns the error before the step. The other outputs get no gradient.
+    pub fn train(&mut self, input: &[f64], output: usize, target: f64) -> f64 {
+        let activations = self.activations(input);
+        let error = target - activations.last().unwrap()[output];
+        // Gradient of half the squared error
+        let mut gradient = vec![0.0; self.layers.last().unwrap().outputs];
+        gradient[output] = -error;
+        self.descend(&activations, gradient);
+        error
+    }
+
+    /// Takes one optimizer step against `gradient`, the gradient of some loss
+    /// with respect to the outputs for `input`.
+    pub fn train_gradient(&mut self, input: &[f64], gradient: &[f64]) {
+        let activations = self.activations(input);
+        self.descend(&activations, gradient.to_vec());
+    }
+
+    /// Backpropagates the output gradient through the layers and updates them.
+    fn descend(&mut self, activations: &[Vec<f64>], mut deltas: Vec<f64>) {
+        self.steps += 1;
+        let (optimizer, steps) = (self.optimizer, self.steps);
+        for (i, layer) in self.layers.iter_mut().enumerate().rev() {
+            let input = &activations[i];
+            // The input of the first layer has no activation to propagate through
+            let previous_count = if i > 0 { layer.inputs } else { 0 };
+            let previous_deltas: Vec<f64> = (0..previous_count)
+                .map(|k| {
+                    let sum: f64 = deltas
+                        .iter()
+                        .enumerate()
+                        .map(|(j, delta)| layer.weights[j * layer.inputs + k] * delta)
+                        .sum();
+                    // Derivative of the tanh which produced this input
+                    sum * (1.0 - input[k] * input[k])
+                })
+                .collect();
+            for (j, delta) in deltas.iter().enumerate() {
+                for (k, x) in input.iter().enumerate() {
+                    let index = j * layer.inputs + k;
+                    let step = optimizer_step(
+                        optimizer,
+                        steps,
+                        &mut layer.moments[index],
+                        delta * x,
+                    );
+                    layer.weights[index] -= step;
+                }
+                let moment = &mut layer.moments[layer.weights.len() + j];
+                layer.biases[j] -= optimizer_step(optimizer, steps, moment, *delta);
+            }
+            deltas = previous_deltas;
+        }
+    }
+
+    /// Writes the network as text: a header line `mlp <optimizer> <learning rate>
+    /// <sizes>...` followed by a line of weights and a line of biases per layer.
+    /// Optimizer state is not saved.
+    pub fn write_to<W: Write + ?Sized>(&self, writer: &mut W) -> io::Result<()> {
+        write!(writer, "mlp {}", self.optimizer)?;
+        for size in self.sizes() {
+            write!(writer, " {}", size)?;
+        }
+        writeln!(writer)?;
+        for layer in &self.layers {
+            write_values(writer, &layer.weights)?;
+            write_values(writer, &layer.biases)?;
+        }
+        Ok(())
+    }
+
+    /// Reads a network written by [`Mlp::write_to`] from the next lines.
+    pub fn read_from<R: BufRead>(reader: &mut R) -> io::Result<Self> {
+        let header = read_line(reader)?;
+        let fields: Vec<&str> = header.split_whitespace().collect();
+        let (optimizer, sizes) = match fields.as_slice() {
+            ["mlp", optimizer, learning_rate, sizes @ ..] if sizes.len() >= 2 => {
+                let learning_rate = parse(learning_rate)?;
+                let optimizer = match *optimizer {
+                    "sgd" => Optimizer::Sgd { learning_rate },
+                    "adam" => Optimizer::Adam { learning_rate },
+                    _ => return Err(invalid_data("unknown optimizer")),
+                };
+                let sizes: Vec<usize> = sizes
+                    .iter()
+                    .map(|size| parse(size))
+                    .collect::<io::Result<_>>()?;
+                if sizes.contains(&0) {
+                    return Err(invalid_data("empty layer"));
+                }
+                (optimizer, sizes)
+            }
+            _ => return Err(invalid_data("missing mlp header")),
+        };
+        let mut layers = Vec::with_capacity(sizes.len() - 1);
+        for pair in sizes.windows(2) {
+            let (inputs, outputs) = (pair[0], pair[1]);
+            let weights = read_values(reader, inputs * outputs)?;
+            let biases = read_values(reader, outputs)?;
+            layers.push(Layer {
+                inputs,
+                outputs,
+                weights,
+                biases,
+                moments: vec![(0.0, 0.0); (inputs + 1) * outputs],
+            });
+        }
+        Ok(Self {
+            layers,
+            optimizer,
+            steps: 0,
+        })
+    }
+}
+
+/// Returns the amount to subtract from a parameter with gradient `gradient`.
+fn optimizer_step(
+    optimizer: Optimizer,
+    steps: i32,
+    (m, v): &mut (f64, f64),
+    gradient: f64,
+) -> f64 {
+    match optimizer {
+        Optimizer::Sgd { learning_rate } => learning_rate * gradient,
+        Optimizer::Adam { learning_rate } => {
+            *m = ADAM_BETA1 * *m + (1.0 - ADAM_BETA1) * gradient;
+            *v = ADAM_BETA2 * *v + (1.0 - ADAM_BETA2) * gradient * gradient;
+            let m_hat = *m / (1.0 - ADAM_BETA1.powi(steps));
+            let v_hat = *v / (1.0 - ADAM_BETA2.powi(steps));
+            learning_rate * m_hat / (v_hat.sqrt() + ADAM_EPSILON)
+        }
+    }
+}
+
+fn invalid_data(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.to_string())
+}
+
+fn parse<T: std::str::FromStr>(value: &str) -> io::Result<T> {
+    value
+        .parse()
+        .map_err(|_| invalid_data(&format!("invalid number `{}`", value)))
+}
+
+fn read_line<R: BufRead>(reader: &mut R) -> io::Result<String> {
+    let mut line = String::new();
+    if reader.read_line(&mut line)? == 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            "network ends early",
+        ));
+    }
+    Ok(line)
+}
+
+fn write_values<W: Write + ?Sized>(writer: &mut W, values: &[f64]) -> io::Result<()> {
+    let values: Vec<String> = values.iter().map(f64::to_string).collect();
+    writeln!(writer, "{}", values.join(" "))
+}
+
+fn read_values<R: BufRead>(reader: &mut R, count: usize) -> io::Result<Vec<f64>> {
+    let values: Vec<f64> = read_line(reader)?
+        .split_whitespace()
+        .map(parse)
+        .collect::<io::Result<_>>()?;
+    if values.len() != count {
+        return Err(invalid_data(&format!(
+            "expected {} values, found {}",
+            count,
+            values.len()
+        )));
+    }
+    Ok(values)
+}
+
+#[test]
+fn mlp_test() {
+    use rand::{rngs::StdRng, SeedableRng};
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut mlp = Mlp::new(
+        &[2, 8, 1],
+        Optimizer::Adam {
+            learning_rate: 0.01,
+        },
+        &mut rng,
+    );
+    // Learn xor
+    let samples = [
+        ([0.0, 0.0], 0.0),
+        ([0.0, 1.0], 1.0),
+        ([1.0, 0.0], 1.0),
+        ([1.0, 1.0], 0.0),
+    ];
+    for _ in 0..2000 {
+        for (input, target) in &samples {
+            mlp.train(input, 0, *target);
+        }
+    }
+    for (input, target) in &samples {
+        assert!((mlp.forward(input)[0] - target).abs() < 0.1);
+    }
+
+    let mut text = Vec::new();
+    mlp.write_to(&mut text).unwrap();
+    let loaded = Mlp::read_from(&mut text.as_slice()).unwrap();
+    assert_eq!(loaded.sizes(), vec![2, 8, 1]);
+    assert_eq!(loaded.forward(&[1.0, 0.0]), mlp.forward(&[1.0, 0.0]));
+    let empty_layer = b"mlp adam 0.01 2 0 1\n";
+    let err = Mlp::read_from(&mut empty_layer.as_slice()).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+}
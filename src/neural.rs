@@ -0,0 +1,293 @@
+use crate::agent::AgentConfig;
+use crate::board::{Board, FieldPosition, FieldState};
+use crate::mlp::{Mlp, Optimizer};
+use crate::player::{Learner, Player, SharedLearner};
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+
+/// Empty, own and opponent flags for each of the nine fields.
+pub(crate) const INPUTS: usize = 27;
+
+/// Agent estimating move values with a small neural network instead of a table.
+///
+/// Boards are one-hot encoded from the point of view of the player to move, so
+/// one network plays both sides and generalizes to boards it has never seen.
+/// The network has one output per field and is trained with Q-learning: every
+/// move's value moves towards the best value of the board the same side faces
+/// next, the last move's towards the final reward.
+#[derive(Clone)]
+pub struct NeuralAgent {
+    network: Mlp,
+    epsilon: f64,
+    epsilon_decay: f64,
+    min_epsilon: f64,
+    rng: StdRng,
+    /// Encoded board and field of the last move of X and of O waiting for their
+    /// update.
+    pending: [Option<(Vec<f64>, usize)>; 2],
+}
+
+impl NeuralAgent {
+    pub fn new(config: AgentConfig, hidden_units: usize, optimizer: Optimizer) -> Self {
+        Self::with_rng(config, hidden_units, optimizer, StdRng::from_entropy())
+    }
+
+    pub fn new_seeded(
+        config: AgentConfig,
+        hidden_units: usize,
+        optimizer: Optimizer,
+        seed: u64,
+    ) -> Self {
+        Self::with_rng(config, hidden_units, optimizer, StdRng::seed_from_u64(seed))
+    }
+
+    fn with_rng(
+        config: AgentConfig,
+        hidden_units: usize,
+        optimizer: Optimizer,
+        mut rng: StdRng,
+    ) -> Self {
+        let network = Mlp::new(&[INPUTS, hidden_units, 9], optimizer, &mut rng);
+        Self::with_network(network, config, rng)
+    }
+
+    fn with_network(network: Mlp, config: AgentConfig, rng: StdRng) -> Self {
+        Self {
+            network,
+            epsilon: config.epsilon,
+            epsilon_decay: config.epsilon_decay,
+            min_epsilon: config.min_epsilon,
+            rng,
+            pending: [None, None],
+        }
+    }
+
+    pub fn network(&self) -> &Mlp {
+        &self.network
+    }
+
+    pub fn epsilon(&self) -> f64 {
+        self.epsilon
+    }
+
+    pub fn set_epsilon(&mut self, epsilon: f64) {
+        self.epsilon = epsilon;
+    }
+
+    /// Forgets the last moves of the current game without training them.
+    pub fn discard_game(&mut self) {
+        self.pending = [None, None];
+    }
+
+    /// Returns every legal move with the value the network gives it.
+    pub fn move_values(&self, board: &Board) -> Vec<(FieldPosition, f64)> {
+        if board.is_finished() {
+            return Vec::new();
+        }
+        let values = self.network.forward(&encode(board));
+        board
+            .get_empty_fields_pos()
+            .into_iter()
+            .map(|pos| (pos, values[field_index(pos)]))
+            .collect()
+    }
+
+    /// Picks a move with the highest value, breaking ties randomly.
+    fn best_move(&mut self, values: &[(FieldPosition, f64)]) -> Option<FieldPosition> {
+        let best = values
+            .iter()
+            .map(|&(_, value)| value)
+            .max_by(f64::total_cmp)?;
+        let best_moves: Vec<FieldPosition> = values
+            .iter()
+            .filter(|&&(_, value)| value == best)
+            .map(|&(pos, _)| pos)
+            .collect();
+        best_moves.choose(&mut self.rng).copied()
+    }
+
+    fn update_pending(&mut self, side: FieldState, target: f64) {
+        if let Some((input, field)) = self.pending[side_index(side)].take() {
+            self.network.train(&input, field, target);
+        }
+    }
+
+    fn decay_epsilon(&mut self) {
+        self.epsilon = (self.epsilon * self.epsilon_decay).max(self.min_epsilon);
+    }
+
+    /// Restarts the random number generator from `seed`.
+    pub fn reseed(&mut self, seed: u64) {
+        self.rng = StdRng::seed_from_u64(seed);
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write_to(&mut writer)?;
+        writer.flush()
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Self::read_from(BufReader::new(File::open(path)?))
+    }
+
+    /// Writes the agent as text: a header line `neural <epsilon> <decay> <min>`
+    /// followed by the network as written by [`Mlp::write_to`].
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writeln!(
+            writer,
+            "neural {} {} {}",
+            self.epsilon, self.epsilon_decay, self.min_epsilon
+        )?;
+        self.network.write_to(writer)
+    }
+
+    pub fn read_from<R: BufRead>(mut reader: R) -> io::Result<Self> {
+        let invalid =
+            || io::Error::new(io::ErrorKind::InvalidData, "missing neural header");
+        let mut header = String::new();
+        reader.read_line(&mut header)?;
+        let config: Vec<f64> = header
+            .strip_prefix("neural ")
+            .ok_or_else(invalid)?
+            .split_whitespace()
+            .map(|value| value.parse().map_err(|_| invalid()))
+            .collect::<io::Result<_>>()?;
+        let is_rate = |rate: f64| (0.0..=1.0).contains(&rate);
+        let config = match config.as_slice() {
+            &[epsilon, epsilon_decay, min_epsilon]
+                if is_rate(epsilon) && is_rate(epsilon_decay) && is_rate(min_epsilon) =>
+            {
+                AgentConfig {
+                    epsilon,
+                    epsilon_decay,
+                    min_epsilon,
+                }
+            }
+            [_, _, _] => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "exploration rate outside 0 to 1",
+                ))
+            }
+            _ => return Err(invalid()),
+        };
+        let network = Mlp::read_from(&mut reader)?;
+        if network.sizes().first() != Some(&INPUTS) || network.sizes().last() != Some(&9)
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "network does not fit the board",
+            ));
+        }
+        Ok(Self::with_network(network, config, StdRng::from_entropy()))
+    }
+}
+
+fn side_index(side: FieldState) -> usize {
+    match side {
+        FieldState::O => 1,
+        _ => 0,
+    }
+}
+
+pub(crate) fn field_index(pos: FieldPosition) -> usize {
+    pos.y * 3 + pos.x
+}
+
+/// One-hot encodes the board from the point of view of the player to move.
+pub(crate) fn encode(board: &Board) -> Vec<f64> {
+    let own = board.next_player();
+    let mut input = vec![0.0; INPUTS];
+    for (i, field) in board.0.iter().flatten().enumerate() {
+        let offset = match *field {
+            FieldState::Empty => 0,
+            field if field == own => 1,
+            _ => 2,
+        };
+        input[i * 3 + offset] = 1.0;
+    }
+    input
+}
+
+impl Player for NeuralAgent {
+    fn choose_move(&mut self, board: &Board) -> Option<FieldPosition> {
+        let values = self.move_values(board);
+        self.best_move(&values)
+    }
+}
+
+impl Learner for NeuralAgent {
+    fn play_training_move(&mut self, board: &Board) -> Option<FieldPosition> {
+        let values = self.move_values(board);
+        let pos = if self.rng.gen_bool(self.epsilon) {
+            values.choose(&mut self.rng).map(|&(pos, _)| pos)
+        } else {
+            self.best_move(&values)
+        }?;
+        let side = board.next_player();
+        let best = values
+            .iter()
+            .map(|&(_, value)| value)
+            .fold(f64::MIN, f64::max);
+        self.update_pending(side, best);
+        self.pending[side_index(side)] = Some((encode(board), field_index(pos)));
+        Some(pos)
+    }
+
+    /// Rewards the last move of whichever sides the agent played.
+    fn give_feedback(&mut self, reward: i32) {
+        if reward > 0 {
+            self.decay_epsilon();
+        }
+        self.update_pending(FieldState::X, reward as f64);
+        self.update_pending(FieldState::O, reward as f64);
+    }
+}
+
+impl SharedLearner for NeuralAgent {
+    fn give_feedback_per_side(&mut self, cross_reward: i32, circle_reward: i32) {
+        if cross_reward > 0 || circle_reward > 0 {
+            self.decay_epsilon();
+        }
+        self.update_pending(FieldState::X, cross_reward as f64);
+        self.update_pending(FieldState::O, circle_reward as f64);
+    }
+}
+
+#[test]
+fn neural_agent_test() {
+    let config = AgentConfig {
+        epsilon: 0.0,
+        ..Default::default()
+    };
+    let optimizer = Optimizer::Adam {
+        learning_rate: 0.01,
+    };
+    let mut agent = NeuralAgent::new_seeded(config, 16, optimizer, 0);
+    let board = Board([
+        [FieldState::X, FieldState::X, FieldState::Empty],
+        [FieldState::O, FieldState::O, FieldState::Empty],
+        [FieldState::Empty, FieldState::Empty, FieldState::Empty],
+    ]);
+    let winning_move = FieldPosition::new(2, 0);
+    for _ in 0..200 {
+        agent.pending[0] = Some((encode(&board), field_index(winning_move)));
+        agent.give_feedback(6);
+    }
+    assert_eq!(agent.choose_move(&board), Some(winning_move));
+
+    let mut text = Vec::new();
+    agent.write_to(&mut text).unwrap();
+    let mut loaded = NeuralAgent::read_from(text.as_slice()).unwrap();
+    assert_eq!(loaded.move_values(&board), agent.move_values(&board));
+    assert_eq!(loaded.choose_move(&board), Some(winning_move));
+
+    let text = String::from_utf8(text).unwrap();
+    let (_, network) = text.split_once('\n').unwrap();
+    let invalid = format!("neural 5 0.9 0.1\n{}", network);
+    let err = NeuralAgent::read_from(invalid.as_bytes()).err().unwrap();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+}
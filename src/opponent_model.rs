@@ -0,0 +1,253 @@
+use crate::board::{Board, FieldPosition, FieldState};
+use crate::evaluation::EvaluationResult;
+use crate::game::{GameEvent, GameState};
+use crate::memory::BoardMemory;
+use crate::minimax::Minimax;
+use crate::player::Player;
+use hashbrown::HashMap;
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+
+/// Player computing a best response to a model of one fixed opponent.
+///
+/// The model counts the replies the opponent was seen to play on every board,
+/// shared by all symmetric versions of a board. Moves are picked by expectimax:
+/// the agent maximizes its expected result while the opponent is assumed to
+/// reply with the observed frequencies, smoothed by `prior` pseudo-games spread
+/// evenly over the legal moves. Unless made unrestricted, the agent only
+/// considers moves which keep the minimax result, so it never does worse than a
+/// perfect player and only picks among equally safe moves the one its model
+/// expects to win most often.
+pub struct OpponentModelAgent {
+    replies: BoardMemory<Vec<(FieldPosition, u32)>>,
+    prior: f64,
+    safe: bool,
+    minimax: Minimax,
+    /// Expected results for the side in the key, cleared where observations
+    /// change them.
+    values: HashMap<(Board, FieldState), f64>,
+    rng: StdRng,
+}
+
+impl OpponentModelAgent {
+    pub fn new(prior: f64) -> Self {
+        Self::with_rng(prior, StdRng::from_entropy())
+    }
+
+    pub fn new_seeded(prior: f64, seed: u64) -> Self {
+        Self::with_rng(prior, StdRng::seed_from_u64(seed))
+    }
+
+    fn with_rng(prior: f64, rng: StdRng) -> Self {
+        Self {
+            replies: BoardMemory::new(),
+            prior,
+            safe: true,
+            minimax: Minimax::new(),
+            values: HashMap::new(),
+            rng,
+        }
+    }
+
+    /// Also considers moves which are worse than the minimax result, trusting
+    /// the model to avoid the punishment.
+    pub fn unrestricted(mut self) -> Self {
+        self.safe = false;
+        self
+    }
+
+    /// Number of distinct boards, up to symmetry, the opponent was seen on.
+    pub fn modeled_boards_count(&self) -> usize {
+        self.replies.len()
+    }
+
+    /// Records that the opponent played `pos` on `board`.
+    pub fn observe(&mut self, board: &Board, pos: FieldPosition) {
+        let (saved_board, transformation) =
+            self.replies.find_or_insert_with(board, Vec::new);
+        let saved_pos = transformation.pos_to_saved(pos);
+        let replies = self.replies.get_mut(&saved_board).unwrap();
+        match replies.iter_mut().find(|(reply, _)| *reply == saved_pos) {
+            Some((_, count)) => *count += 1,
+            None => replies.push((saved_pos, 1)),
+        }
+        // Only boards up to this one can lead to it
+        let empty_field_count = board.field_state_count(FieldState::Empty);
+        self.values.retain(|(board, _), _| {
+            board.field_state_count(FieldState::Empty) < empty_field_count
+        });
+    }
+
+    /// Returns every legal move of `board` with the probability the model gives
+    /// the opponent playing it.
+    pub fn reply_probabilities(&self, board: &Board) -> Vec<(FieldPosition, f64)> {
+        let moves = board.get_empty_fields_pos();
+        let counts: Vec<u32> = match self.replies.find(board) {
+            Some((saved_board, transformation)) => {
+                let replies = self.replies.get(&saved_board).unwrap();
+                moves
+                    .iter()
+                    .map(|&pos| {
+                        let saved_pos = transformation.pos_to_saved(pos);
+                        replies
+                            .iter()
+                            .find(|(reply, _)| *reply == saved_pos)
+                            .map_or(0, |&(_, count)| count)
+                    })
+                    .collect()
+            }
+            None => vec![0; moves.len()],
+        };
+        let legal = moves.len() as f64;
+        let total = counts.iter().sum::<u32>() as f64 + self.prior * legal;
+        moves
+            .into_iter()
+            .zip(counts)
+            .map(|(pos, count)| {
+                let probability = if total > 0.0 {
+                    (count as f64 + self.prior) / total
+                } else {
+                    1.0 / legal
+                };
+                (pos, probability)
+            })
+            .collect()
+    }
+
+    /// Returns the moves the agent considers on `board`.
+    fn candidates(&mut self, board: &Board) -> Vec<FieldPosition> {
+        if self.safe {
+            self.minimax.best_moves(board)
+        } else {
+            board.get_empty_fields_pos()
+        }
+    }
+
+    /// Expected result for `side` from `board` on: 1 for a win, -1 for a loss.
+    fn value(&mut self, board: &Board, side: FieldState) -> f64 {
+        let winner = board.find_winner();
+        if winner != FieldState::Empty {
+            return if winner == side { 1.0 } else { -1.0 };
+        }
+        if board.is_finished() {
+            return 0.0;
+        }
+        if let Some(&value) = self.values.get(&(board.clone(), side)) {
+            return value;
+        }
+        let value = if board.next_player() == side {
+            self.candidates(board)
+                .into_iter()
+                .map(|pos| self.move_value(board, pos, side))
+                .fold(f64::MIN, f64::max)
+        } else {
+            self.reply_probabilities(board)
+                .into_iter()
+                .map(|(pos, probability)| probability * self.move_value(board, pos, side))
+                .sum()
+        };
+        self.values.insert((board.clone(), side), value);
+        value
+    }
+
+    fn move_value(&mut self, board: &Board, pos: FieldPosition, side: FieldState) -> f64 {
+        let mut next = board.clone();
+        next.play_move_at(pos);
+        self.value(&next, side)
+    }
+
+    /// Returns the moves the agent considers with their expected results.
+    pub fn move_values(&mut self, board: &Board) -> Vec<(FieldPosition, f64)> {
+        if board.is_finished() {
+            return Vec::new();
+        }
+        let side = board.next_player();
+        self.candidates(board)
+            .into_iter()
+            .map(|pos| (pos, self.move_value(board, pos, side)))
+            .collect()
+    }
+}
+
+impl Player for OpponentModelAgent {
+    fn choose_move(&mut self, board: &Board) -> Option<FieldPosition> {
+        let values = self.move_values(board);
+        let best = values
+            .iter()
+            .map(|&(_, value)| value)
+            .max_by(f64::total_cmp)?;
+        let best_moves: Vec<FieldPosition> = values
+            .into_iter()
+            // Tolerates rounding in the sums of equal expectations
+            .filter(|&(_, value)| value >= best - 1e-9)
+            .map(|(pos, _)| pos)
+            .collect();
+        best_moves.choose(&mut self.rng).copied()
+    }
+}
+
+/// Plays `games` games against `opponent`, switching sides every game, while
+/// the agent models every move the opponent makes. Returns the agent's results.
+pub fn model_opponent(
+    agent: &mut OpponentModelAgent,
+    opponent: &mut dyn Player,
+    games: usize,
+) -> EvaluationResult {
+    let mut result = EvaluationResult::default();
+    for i in 0..games {
+        let agent_side = if i % 2 == 0 {
+            FieldState::X
+        } else {
+            FieldState::O
+        };
+        GameState::new().run_new(|event, board_state| match event {
+            GameEvent::CrossWon | GameEvent::CircleWon | GameEvent::Draw => {
+                result.record_winner(board_state.find_winner(), agent_side);
+            }
+            GameEvent::CrossTurn | GameEvent::CircleTurn => {
+                let position = if board_state.next_player() == agent_side {
+                    agent.choose_move(board_state).unwrap()
+                } else {
+                    let position = opponent.choose_move(board_state).unwrap();
+                    agent.observe(board_state, position);
+                    position
+                };
+                board_state.play_move_at(position);
+            }
+            GameEvent::InvalidBoard => unreachable!(),
+        });
+    }
+    result
+}
+
+#[test]
+fn opponent_model_test() {
+    use crate::evaluation;
+    use crate::minimax::PerfectPlayer;
+    use crate::player::HeuristicPlayer;
+
+    let mut agent = OpponentModelAgent::new_seeded(1.0, 0);
+    let probabilities = agent.reply_probabilities(&Board::EMPTY);
+    assert!(probabilities
+        .iter()
+        .all(|&(_, p)| (p - 1.0 / 9.0).abs() < 1e-12));
+    let mut board = Board::EMPTY;
+    board.play_move_at(FieldPosition::new(0, 0));
+    agent.observe(&board, FieldPosition::new(1, 0));
+    // Symmetric boards share their counts
+    let rotated = agent.reply_probabilities(&board.get_rotated_90_clockwise());
+    let observed = rotated
+        .iter()
+        .filter(|&&(_, p)| (p - 2.0 / 9.0).abs() < 1e-12);
+    assert_eq!(observed.count(), 1);
+
+    let mut opponent = HeuristicPlayer::new_seeded(0);
+    model_opponent(&mut agent, &mut opponent, 200);
+    let exploited = evaluation::evaluate_as_cross(&mut agent, &mut opponent, 50)
+        + evaluation::evaluate_as_circle(&mut agent, &mut opponent, 50);
+    let mut perfect = PerfectPlayer::new_seeded(0);
+    let minimax = evaluation::evaluate_as_cross(&mut perfect, &mut opponent, 50)
+        + evaluation::evaluate_as_circle(&mut perfect, &mut opponent, 50);
+    // The heuristic player blocks every single threat but walks into forks
+    assert_eq!(exploited.losses, 0);
+    assert!(exploited.wins > minimax.wins);
+}
@@ -0,0 +1,178 @@
+use crate::agent::Agent;
+use crate::board::{Board, FieldPosition, FieldState};
+use crate::mcts::MctsPlayer;
+use crate::minimax::{Difficulty, MinimaxPlayer, PerfectPlayer};
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+
+/// Anything that can pick a move on a board.
+pub trait Player {
+    fn choose_move(&mut self, board: &Board) -> Option<FieldPosition>;
+}
+
+/// Player which learns from the games it plays.
+pub trait Learner: Player {
+    /// Picks a move while training, possibly exploring, and remembers it for
+    /// the feedback at the end of the game.
+    fn play_training_move(&mut self, board: &Board) -> Option<FieldPosition>;
+
+    /// Ends the game, rewarding the moves played since the last feedback.
+    fn give_feedback(&mut self, reward: i32);
+}
+
+/// Learner which plays both sides of its training games with the same values.
+pub trait SharedLearner: Learner {
+    /// Ends the game, rewarding the moves of X and of O separately.
+    fn give_feedback_per_side(&mut self, cross_reward: i32, circle_reward: i32);
+}
+
+impl Learner for Agent {
+    fn play_training_move(&mut self, board: &Board) -> Option<FieldPosition> {
+        self.play_greedy_exploration(board)
+    }
+
+    fn give_feedback(&mut self, reward: i32) {
+        Agent::give_feedback(self, reward)
+    }
+}
+
+impl SharedLearner for Agent {
+    fn give_feedback_per_side(&mut self, cross_reward: i32, circle_reward: i32) {
+        Agent::give_feedback_per_side(self, cross_reward, circle_reward)
+    }
+}
+
+/// Agents play greedily, without exploring or learning, when used as a `Player`.
+impl Player for Agent {
+    fn choose_move(&mut self, board: &Board) -> Option<FieldPosition> {
+        self.play_greedy(board)
+    }
+}
+
+/// Player picking a uniformly random empty field.
+pub struct RandomPlayer {
+    rng: StdRng,
+}
+
+impl RandomPlayer {
+    pub fn new() -> Self {
+        Self {
+            rng: StdRng::from_entropy(),
+        }
+    }
+
+    pub fn new_seeded(seed: u64) -> Self {
+        Self {
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+}
+
+impl Default for RandomPlayer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Player for RandomPlayer {
+    fn choose_move(&mut self, board: &Board) -> Option<FieldPosition> {
+        board.get_empty_fields_pos().choose(&mut self.rng).copied()
+    }
+}
+
+/// Rule based player: wins if it can, otherwise blocks the opponent's win, then
+/// prefers the center over corners over edges.
+pub struct HeuristicPlayer {
+    rng: StdRng,
+}
+
+impl HeuristicPlayer {
+    pub fn new() -> Self {
+        Self {
+            rng: StdRng::from_entropy(),
+        }
+    }
+
+    pub fn new_seeded(seed: u64) -> Self {
+        Self {
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+}
+
+impl Default for HeuristicPlayer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Player for HeuristicPlayer {
+    fn choose_move(&mut self, board: &Board) -> Option<FieldPosition> {
+        let empty_fields = board.get_empty_fields_pos();
+        let me = board.next_player();
+        let completes_line = |pos: FieldPosition, player: FieldState| {
+            let mut next = board.clone();
+            next.0[pos.y][pos.x] = player;
+            next.find_winner() == player
+        };
+        for player in [me, me.opponent()] {
+            let winning: Vec<FieldPosition> = empty_fields
+                .iter()
+                .copied()
+                .filter(|&pos| completes_line(pos, player))
+                .collect();
+            if let Some(&pos) = winning.choose(&mut self.rng) {
+                return Some(pos);
+            }
+        }
+        let rank = |pos: &FieldPosition| match (pos.x, pos.y) {
+            (1, 1) => 0,
+            (0 | 2, 0 | 2) => 1,
+            _ => 2,
+        };
+        let best_rank = empty_fields.iter().map(rank).min()?;
+        let best: Vec<FieldPosition> = empty_fields
+            .into_iter()
+            .filter(|pos| rank(pos) == best_rank)
+            .collect();
+        best.choose(&mut self.rng).copied()
+    }
+}
+
+/// Builds a scripted player from a spec: `random`, `heuristic`, `perfect`, `easy`,
+/// `medium`, `hard`, `minimax[:<depth>[:<blunder probability>]]` or `mcts[:<iterations>]`.
+pub fn from_spec(spec: &str, seed: u64) -> Result<Box<dyn Player>, String> {
+    let mut parts = spec.split(':');
+    let kind = parts.next().unwrap_or_default();
+    let params: Vec<&str> = parts.collect();
+    let param = |index: usize, default: &'static str| {
+        params.get(index).copied().unwrap_or(default)
+    };
+    let invalid =
+        |value: &str| format!("invalid number `{}` in player `{}`", value, spec);
+    match kind {
+        "random" => Ok(Box::new(RandomPlayer::new_seeded(seed))),
+        "heuristic" => Ok(Box::new(HeuristicPlayer::new_seeded(seed))),
+        "perfect" => Ok(Box::new(PerfectPlayer::new_seeded(seed))),
+        "minimax" => {
+            let (depth, blunders) = (param(0, "9"), param(1, "0"));
+            let depth = depth.parse().map_err(|_| invalid(depth))?;
+            let blunders = blunders
+                .parse()
+                .ok()
+                .filter(|probability| (0.0..=1.0).contains(probability))
+                .ok_or_else(|| invalid(blunders))?;
+            Ok(Box::new(
+                MinimaxPlayer::new_seeded(depth, seed).with_blunders(blunders),
+            ))
+        }
+        "mcts" => {
+            let iterations = param(0, "1000");
+            let iterations = iterations.parse().map_err(|_| invalid(iterations))?;
+            Ok(Box::new(MctsPlayer::new_seeded(iterations, seed)))
+        }
+        _ => match kind.parse::<Difficulty>() {
+            Ok(difficulty) => Ok(Box::new(difficulty.player_seeded(seed))),
+            Err(_) => Err(format!("unknown player `{}`", spec)),
+        },
+    }
+}
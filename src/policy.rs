@@ -0,0 +1,319 @@
+use crate::board::{Board, FieldPosition};
+use crate::memory::BoardMemory;
+use crate::mlp::{Mlp, Optimizer};
+use crate::neural::{self, INPUTS};
+use crate::player::{Learner, Player};
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+
+/// How a [`PolicyAgent`] weighs each move of a finished game.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PolicyMethod {
+    /// By the final result minus a learned baseline value of the board
+    /// (REINFORCE with baseline).
+    Reinforce,
+    /// By the one-step advantage: the learned value of the next board the agent
+    /// moved on, or the final result, minus the value of the board.
+    ActorCritic,
+}
+
+impl std::fmt::Display for PolicyMethod {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PolicyMethod::Reinforce => write!(f, "reinforce"),
+            PolicyMethod::ActorCritic => write!(f, "actor-critic"),
+        }
+    }
+}
+
+/// Parameterization of the learned move preferences and board values of a
+/// [`PolicyAgent`] or an [`AlphaZeroAgent`](crate::alphazero::AlphaZeroAgent).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PolicyModel {
+    /// One preference per board and move and one value per board, shared by all
+    /// symmetric versions of a board.
+    Table { learning_rate: f64 },
+    /// A network with one preference output per field and a second network
+    /// with a single value output, both seeing boards from the side to move.
+    Neural {
+        hidden_units: usize,
+        optimizer: Optimizer,
+    },
+}
+
+#[derive(Clone)]
+enum Parameters {
+    Table {
+        learning_rate: f64,
+        /// Preferences of every legal move by saved board and saved position.
+        preferences: BoardMemory<Vec<(FieldPosition, f64)>>,
+        values: BoardMemory<f64>,
+    },
+    Neural {
+        policy: Mlp,
+        value: Mlp,
+    },
+}
+
+impl Parameters {
+    fn new(model: PolicyModel, rng: &mut StdRng) -> Self {
+        match model {
+            PolicyModel::Table { learning_rate } => Parameters::Table {
+                learning_rate,
+                preferences: BoardMemory::new(),
+                values: BoardMemory::new(),
+            },
+            PolicyModel::Neural {
+                hidden_units,
+                optimizer,
+            } => Parameters::Neural {
+                policy: Mlp::new(&[INPUTS, hidden_units, 9], optimizer, rng),
+                value: Mlp::new(&[INPUTS, hidden_units, 1], optimizer, rng),
+            },
+        }
+    }
+
+    /// Preferences of the legal moves of a board in its original orientation.
+    fn preferences(&self, board: &Board) -> Vec<(FieldPosition, f64)> {
+        match self {
+            Parameters::Table { preferences, .. } => match preferences.find(board) {
+                Some((saved_board, transformation)) => preferences
+                    .get(&saved_board)
+                    .unwrap()
+                    .iter()
+                    .map(|&(pos, preference)| {
+                        (transformation.pos_to_original(pos), preference)
+                    })
+                    .collect(),
+                None => board
+                    .get_empty_fields_pos()
+                    .into_iter()
+                    .map(|pos| (pos, 0.0))
+                    .collect(),
+            },
+            Parameters::Neural { policy, .. } => {
+                let outputs = policy.forward(&neural::encode(board));
+                board
+                    .get_empty_fields_pos()
+                    .into_iter()
+                    .map(|pos| (pos, outputs[neural::field_index(pos)]))
+                    .collect()
+            }
+        }
+    }
+
+    fn value(&self, board: &Board) -> f64 {
+        match self {
+            Parameters::Table { values, .. } => {
+                values.lookup(board).copied().unwrap_or(0.0)
+            }
+            Parameters::Neural { value, .. } => value.forward(&neural::encode(board))[0],
+        }
+    }
+
+    fn update_value(&mut self, board: &Board, target: f64) {
+        match self {
+            Parameters::Table {
+                learning_rate,
+                values,
+                ..
+            } => {
+                let (saved_board, _) = values.find_or_insert_with(board, || 0.0);
+                let value = values.get_mut(&saved_board).unwrap();
+                *value += *learning_rate * (target - *value);
+            }
+            Parameters::Neural { value, .. } => {
+                value.train(&neural::encode(board), 0, target);
+            }
+        }
+    }
+
+    /// Follows the gradient of the log probability of playing `pos` on `board`,
+    /// scaled by `advantage`.
+    fn update_policy(&mut self, board: &Board, pos: FieldPosition, advantage: f64) {
+        let probabilities = softmax(&self.preferences(board));
+        // Gradient of the log softmax with respect to every preference
+        let gradients = probabilities.into_iter().map(|(other, probability)| {
+            let chosen = if other == pos { 1.0 } else { 0.0 };
+            (other, advantage * (chosen - probability))
+        });
+        match self {
+            Parameters::Table {
+                learning_rate,
+                preferences,
+                ..
+            } => {
+                let (saved_board, transformation) =
+                    preferences.find_or_insert_with(board, || {
+                        board
+                            .get_empty_fields_pos()
+                            .into_iter()
+                            .map(|pos| (pos, 0.0))
+                            .collect()
+                    });
+                let saved = preferences.get_mut(&saved_board).unwrap();
+                for (other, gradient) in gradients {
+                    let saved_pos = transformation.pos_to_saved(other);
+                    let (_, preference) =
+                        saved.iter_mut().find(|(pos, _)| *pos == saved_pos).unwrap();
+                    *preference += *learning_rate * gradient;
+                }
+            }
+            Parameters::Neural { policy, .. } => {
+                // The network minimizes, so it gets the negated gradient
+                let mut output_gradient = vec![0.0; 9];
+                for (other, gradient) in gradients {
+                    output_gradient[neural::field_index(other)] = -gradient;
+                }
+                policy.train_gradient(&neural::encode(board), &output_gradient);
+            }
+        }
+    }
+}
+
+/// Turns preferences into probabilities.
+pub(crate) fn softmax(preferences: &[(FieldPosition, f64)]) -> Vec<(FieldPosition, f64)> {
+    let max = preferences
+        .iter()
+        .map(|&(_, preference)| preference)
+        .fold(f64::MIN, f64::max);
+    let exps: Vec<f64> = preferences
+        .iter()
+        .map(|&(_, preference)| (preference - max).exp())
+        .collect();
+    let sum: f64 = exps.iter().sum();
+    preferences
+        .iter()
+        .zip(exps)
+        .map(|(&(pos, _), exp)| (pos, exp / sum))
+        .collect()
+}
+
+/// Policy-gradient agent picking moves from a softmax over move preferences.
+///
+/// Instead of exploring with epsilon the agent samples every training move from
+/// its policy, and learns at the end of each game by moving the preferences of
+/// its moves along the gradient of their log probability, weighted as chosen by
+/// the [`PolicyMethod`]. The learned board values only serve as baseline or
+/// critic. As a [`Player`] it plays its most probable move.
+#[derive(Clone)]
+pub struct PolicyAgent {
+    method: PolicyMethod,
+    parameters: Parameters,
+    rng: StdRng,
+    /// Boards the agent moved on this game, with the moves it picked.
+    episode: Vec<(Board, FieldPosition)>,
+}
+
+impl PolicyAgent {
+    pub fn new(method: PolicyMethod, model: PolicyModel) -> Self {
+        Self::with_rng(method, model, StdRng::from_entropy())
+    }
+
+    pub fn new_seeded(method: PolicyMethod, model: PolicyModel, seed: u64) -> Self {
+        Self::with_rng(method, model, StdRng::seed_from_u64(seed))
+    }
+
+    fn with_rng(method: PolicyMethod, model: PolicyModel, mut rng: StdRng) -> Self {
+        Self {
+            method,
+            parameters: Parameters::new(model, &mut rng),
+            rng,
+            episode: Vec::with_capacity(5),
+        }
+    }
+
+    pub fn method(&self) -> PolicyMethod {
+        self.method
+    }
+
+    /// Returns every legal move with the probability the policy gives it.
+    pub fn move_probabilities(&self, board: &Board) -> Vec<(FieldPosition, f64)> {
+        if board.is_finished() {
+            return Vec::new();
+        }
+        softmax(&self.parameters.preferences(board))
+    }
+
+    /// Learned value of a board the agent is to move on.
+    pub fn board_value(&self, board: &Board) -> f64 {
+        self.parameters.value(board)
+    }
+}
+
+impl Player for PolicyAgent {
+    fn choose_move(&mut self, board: &Board) -> Option<FieldPosition> {
+        let probabilities = self.move_probabilities(board);
+        let best = probabilities
+            .iter()
+            .map(|&(_, probability)| probability)
+            .max_by(f64::total_cmp)?;
+        let best_moves: Vec<FieldPosition> = probabilities
+            .into_iter()
+            .filter(|&(_, probability)| probability == best)
+            .map(|(pos, _)| pos)
+            .collect();
+        best_moves.choose(&mut self.rng).copied()
+    }
+}
+
+impl Learner for PolicyAgent {
+    fn play_training_move(&mut self, board: &Board) -> Option<FieldPosition> {
+        let probabilities = self.move_probabilities(board);
+        let &(pos, _) = probabilities
+            .choose_weighted(&mut self.rng, |&(_, probability)| probability)
+            .ok()?;
+        self.episode.push((board.clone(), pos));
+        Some(pos)
+    }
+
+    fn give_feedback(&mut self, reward: i32) {
+        let mut target = reward as f64;
+        // Backwards, so actor-critic targets already include this game's result
+        for (board, pos) in self.episode.drain(..).rev() {
+            let advantage = target - self.parameters.value(&board);
+            self.parameters.update_value(&board, target);
+            self.parameters.update_policy(&board, pos, advantage);
+            if self.method == PolicyMethod::ActorCritic {
+                target = self.parameters.value(&board);
+            }
+        }
+    }
+}
+
+#[test]
+fn policy_agent_test() {
+    use crate::board::FieldState;
+    let board = Board([
+        [FieldState::X, FieldState::X, FieldState::Empty],
+        [FieldState::O, FieldState::O, FieldState::Empty],
+        [FieldState::Empty, FieldState::Empty, FieldState::Empty],
+    ]);
+    let winning_move = FieldPosition::new(2, 0);
+    let models = [
+        PolicyModel::Table { learning_rate: 0.1 },
+        PolicyModel::Neural {
+            hidden_units: 16,
+            optimizer: Optimizer::Adam {
+                learning_rate: 0.01,
+            },
+        },
+    ];
+    for model in models {
+        let mut agent = PolicyAgent::new_seeded(PolicyMethod::Reinforce, model, 0);
+        let probability = |agent: &PolicyAgent| {
+            agent
+                .move_probabilities(&board)
+                .into_iter()
+                .find(|&(pos, _)| pos == winning_move)
+                .unwrap()
+                .1
+        };
+        let before = probability(&agent);
+        for _ in 0..50 {
+            agent.episode.push((board.clone(), winning_move));
+            agent.give_feedback(6);
+        }
+        assert!(probability(&agent) > before.max(0.5));
+        assert_eq!(agent.choose_move(&board), Some(winning_move));
+    }
+}
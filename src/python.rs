@@ -0,0 +1,451 @@
+use crate::agent::Rewards;
+use crate::board::{Board, FieldPosition, FieldState};
+use crate::env::{self, Observation, SideChoice, Step};
+use crate::game::GameEvent;
+use crate::player;
+use crate::saved::SavedAgent;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use std::collections::HashMap;
+
+/// Python module exposing boards, the Gym-style environment and saved agents.
+///
+/// Actions are the fields numbered 0 to 8 row by row, boards are written as nine
+/// `X`, `O` and `.` characters and sides as `"X"` and `"O"`.
+#[pymodule]
+fn tic_tac_toe_learning_ai(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<PyBoard>()?;
+    module.add_class::<PyEnv>()?;
+    module.add_class::<PyAgent>()?;
+    Ok(())
+}
+
+fn side_name(side: FieldState) -> Option<&'static str> {
+    match side {
+        FieldState::X => Some("X"),
+        FieldState::O => Some("O"),
+        FieldState::Empty => None,
+    }
+}
+
+fn position(action: usize) -> PyResult<FieldPosition> {
+    match action {
+        action if action < env::ACTIONS => Ok(FieldPosition::new(action % 3, action / 3)),
+        _ => Err(PyValueError::new_err(format!(
+            "action {} is out of range",
+            action
+        ))),
+    }
+}
+
+fn action(pos: FieldPosition) -> usize {
+    pos.y * 3 + pos.x
+}
+
+/// Tic-tac-toe board, empty or decoded from nine `X`, `O` and `.` characters.
+#[pyclass(name = "Board", eq, skip_from_py_object)]
+#[derive(Clone, PartialEq)]
+pub struct PyBoard {
+    board: Board,
+}
+
+#[pymethods]
+impl PyBoard {
+    #[new]
+    #[pyo3(signature = (code = None))]
+    fn new(code: Option<&str>) -> PyResult<Self> {
+        let Some(code) = code else {
+            return Ok(Self {
+                board: Board::EMPTY,
+            });
+        };
+        let invalid = || PyValueError::new_err(format!("invalid board `{}`", code));
+        let board = Board::from_code(code).ok_or_else(invalid)?;
+        if GameEvent::of(&board) == GameEvent::InvalidBoard {
+            return Err(invalid());
+        }
+        Ok(Self { board })
+    }
+
+    /// The board as nine `X`, `O` and `.` characters.
+    #[getter]
+    fn code(&self) -> String {
+        self.board.to_code()
+    }
+
+    fn next_player(&self) -> &'static str {
+        side_name(self.board.next_player()).unwrap()
+    }
+
+    /// `"X"` or `"O"` if somebody has three in a row, otherwise `None`.
+    fn winner(&self) -> Option<&'static str> {
+        side_name(self.board.find_winner())
+    }
+
+    fn is_finished(&self) -> bool {
+        self.board.is_finished()
+    }
+
+    /// `"X won"`, `"O won"`, `"draw"`, `"X to move"` or `"O to move"`.
+    fn state(&self) -> String {
+        GameEvent::of(&self.board).to_string()
+    }
+
+    fn legal_actions(&self) -> Vec<usize> {
+        match self.board.is_finished() {
+            true => Vec::new(),
+            false => self
+                .board
+                .get_empty_fields_pos()
+                .into_iter()
+                .map(action)
+                .collect(),
+        }
+    }
+
+    /// Plays `action` for the side to move.
+    fn play(&mut self, action: usize) -> PyResult<()> {
+        let pos = position(action)?;
+        if self.board.is_finished() {
+            return Err(PyValueError::new_err("the game is over"));
+        }
+        if self.board.0[pos.y][pos.x] != FieldState::Empty {
+            return Err(PyValueError::new_err(format!(
+                "field of action {} is taken",
+                action
+            )));
+        }
+        self.board.play_move_at(pos);
+        Ok(())
+    }
+
+    /// The board with the given action played, leaving this board unchanged.
+    fn after(&self, action: usize) -> PyResult<Self> {
+        let mut board = self.clone();
+        board.play(action)?;
+        Ok(board)
+    }
+
+    fn copy(&self) -> Self {
+        self.clone()
+    }
+
+    fn rotated(&self) -> Self {
+        Self {
+            board: self.board.get_rotated_90_clockwise(),
+        }
+    }
+
+    fn flipped_horizontally(&self) -> Self {
+        Self {
+            board: self.board.get_flipped_horizontally(),
+        }
+    }
+
+    fn flipped_vertically(&self) -> Self {
+        Self {
+            board: self.board.get_flipped_vertically(),
+        }
+    }
+
+    fn color_swapped(&self) -> Self {
+        Self {
+            board: self.board.get_color_swapped(),
+        }
+    }
+
+    /// The eight rotations and reflections of the board, starting with the board
+    /// itself. Symmetric boards appear more than once.
+    fn symmetries(&self) -> Vec<Self> {
+        let rotated = self.board.get_rotated_90_clockwise();
+        let rotated_twice = rotated.get_rotated_90_clockwise();
+        [
+            self.board.clone(),
+            rotated_twice.get_rotated_90_clockwise(),
+            rotated_twice,
+            rotated,
+            self.board.get_flipped_horizontally(),
+            self.board.get_flipped_vertically(),
+            self.board.get_flipped_diagonally_southwest_northeast(),
+            self.board.get_flipped_diagonally_northwest_southeast(),
+        ]
+        .into_iter()
+        .map(|board| Self { board })
+        .collect()
+    }
+
+    fn __str__(&self) -> String {
+        self.board.to_string()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Board('{}')", self.board.to_code())
+    }
+}
+
+/// Gym-style environment playing against a built-in opponent, see [`env::Env`].
+///
+/// `opponent` is a player spec like `"perfect"` or `"minimax:2"` or an `Agent`,
+/// `side` is `"X"`, `"O"` or `"alternate"`. Observations are dicts with the
+/// `board`, its one-hot `features` from the learner's point of view and the
+/// `action_mask`; the info of a step holds the `state` of the game and the
+/// `opponent_action`.
+#[pyclass(name = "Env", unsendable)]
+pub struct PyEnv {
+    env: env::Env,
+}
+
+impl PyEnv {
+    fn observation<'py>(
+        py: Python<'py>,
+        observation: Observation,
+    ) -> PyResult<Bound<'py, PyDict>> {
+        let dict = PyDict::new(py);
+        dict.set_item("features", observation.features())?;
+        dict.set_item("action_mask", observation.action_mask().to_vec())?;
+        dict.set_item(
+            "board",
+            PyBoard {
+                board: observation.board,
+            },
+        )?;
+        Ok(dict)
+    }
+}
+
+#[pymethods]
+impl PyEnv {
+    #[new]
+    #[pyo3(signature = (opponent = None, side = "X", seed = None, win = 1, loss = -1, draw = 0))]
+    fn new(
+        opponent: Option<&Bound<'_, PyAny>>,
+        side: &str,
+        seed: Option<u64>,
+        win: i32,
+        loss: i32,
+        draw: i32,
+    ) -> PyResult<Self> {
+        let seed = seed.unwrap_or_else(rand::random);
+        let opponent = match opponent {
+            None => player::from_spec("perfect", seed),
+            Some(opponent) => match opponent.cast::<PyAgent>() {
+                Ok(agent) => {
+                    let mut agent = agent.borrow().agent.clone();
+                    agent.reseed(seed);
+                    Ok(agent.into_player())
+                }
+                Err(_) => player::from_spec(&opponent.extract::<String>()?, seed),
+            },
+        }
+        .map_err(PyValueError::new_err)?;
+        let side_choice = match side {
+            "X" | "x" => SideChoice::Cross,
+            "O" | "o" => SideChoice::Circle,
+            "alternate" => SideChoice::Alternate,
+            _ => return Err(PyValueError::new_err(format!("invalid side `{}`", side))),
+        };
+        let env = env::Env::new(opponent)
+            .with_side(side_choice)
+            .with_rewards(Rewards { win, loss, draw });
+        Ok(Self { env })
+    }
+
+    /// The side the learner plays in the current game.
+    #[getter]
+    fn side(&self) -> &'static str {
+        side_name(self.env.side()).unwrap()
+    }
+
+    #[getter]
+    fn board(&self) -> PyBoard {
+        PyBoard {
+            board: self.env.board().clone(),
+        }
+    }
+
+    fn reset<'py>(&mut self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        Self::observation(py, self.env.reset())
+    }
+
+    /// Returns `(observation, reward, done, info)`.
+    #[allow(clippy::type_complexity)]
+    fn step<'py>(
+        &mut self,
+        py: Python<'py>,
+        action: usize,
+    ) -> PyResult<(Bound<'py, PyDict>, f64, bool, Bound<'py, PyDict>)> {
+        let Step {
+            observation,
+            reward,
+            done,
+            info: step_info,
+        } = self.env.step(action).map_err(PyValueError::new_err)?;
+        let info = PyDict::new(py);
+        info.set_item("state", step_info.event.to_string())?;
+        info.set_item("opponent_action", step_info.opponent_action)?;
+        Ok((Self::observation(py, observation)?, reward, done, info))
+    }
+}
+
+/// Agent saved by the binary, either a table or a neural agent.
+#[pyclass(name = "Agent", unsendable)]
+pub struct PyAgent {
+    agent: SavedAgent,
+}
+
+#[pymethods]
+impl PyAgent {
+    #[staticmethod]
+    #[pyo3(signature = (path, seed = None))]
+    fn load(path: &str, seed: Option<u64>) -> PyResult<Self> {
+        let mut agent = SavedAgent::load(path)?;
+        if let Some(seed) = seed {
+            agent.reseed(seed);
+        }
+        Ok(Self { agent })
+    }
+
+    fn save(&self, path: &str) -> PyResult<()> {
+        Ok(self.agent.save(path)?)
+    }
+
+    /// `"table"` or `"neural"`.
+    #[getter]
+    fn kind(&self) -> &'static str {
+        match self.agent {
+            SavedAgent::Table(_) => "table",
+            SavedAgent::Neural(_) => "neural",
+        }
+    }
+
+    #[getter]
+    fn epsilon(&self) -> f64 {
+        self.agent.epsilon()
+    }
+
+    #[setter]
+    fn set_epsilon(&mut self, epsilon: f64) -> PyResult<()> {
+        if !(0.0..=1.0).contains(&epsilon) {
+            return Err(PyValueError::new_err(format!(
+                "invalid epsilon {}",
+                epsilon
+            )));
+        }
+        self.agent.set_epsilon(epsilon);
+        Ok(())
+    }
+
+    /// Number of boards a table agent has memorized, `None` for neural agents.
+    #[getter]
+    fn memorized_boards(&self) -> Option<usize> {
+        match &self.agent {
+            SavedAgent::Table(agent) => Some(agent.memorized_boards_count()),
+            SavedAgent::Neural(_) => None,
+        }
+    }
+
+    /// The agent's greedy move, `None` on a finished board.
+    fn choose_move(&mut self, board: &PyBoard) -> Option<usize> {
+        if board.board.is_finished() {
+            return None;
+        }
+        self.agent.learner().choose_move(&board.board).map(action)
+    }
+
+    /// Value of every move the agent knows on `board`: the bias for table agents,
+    /// the network output for neural agents.
+    fn move_values(&self, board: &PyBoard) -> HashMap<usize, f64> {
+        match &self.agent {
+            SavedAgent::Table(agent) => agent
+                .get_actions_from_board(&board.board)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|agent_action| {
+                    (action(agent_action.field_pos), agent_action.bias as f64)
+                })
+                .collect(),
+            SavedAgent::Neural(agent) => agent
+                .move_values(&board.board)
+                .into_iter()
+                .map(|(pos, value)| (action(pos), value))
+                .collect(),
+        }
+    }
+
+    /// Number of finished games each known move was played in, empty for neural
+    /// agents.
+    fn visits(&self, board: &PyBoard) -> HashMap<usize, u32> {
+        match &self.agent {
+            SavedAgent::Table(agent) => agent
+                .get_actions_from_board(&board.board)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|agent_action| (action(agent_action.field_pos), agent_action.visits))
+                .collect(),
+            SavedAgent::Neural(_) => HashMap::new(),
+        }
+    }
+}
+
+#[test]
+fn python_test() {
+    use std::ffi::CString;
+
+    let dir = std::env::temp_dir().join(format!("ttt-python-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("table.agent");
+    std::fs::write(&path, "agent 0 0.999 0.1\n......... 1,1:6:2 0,0:-3:1\n").unwrap();
+
+    Python::initialize();
+    Python::attach(|py| {
+        let module = PyModule::new(py, "tic_tac_toe_learning_ai").unwrap();
+        tic_tac_toe_learning_ai(&module).unwrap();
+        let globals = PyDict::new(py);
+        globals.set_item("ttt", module).unwrap();
+        globals.set_item("path", path.to_str().unwrap()).unwrap();
+        let code = CString::new(
+            r#"
+board = ttt.Board()
+board.play(4)
+assert board.code == "....X...." and board.next_player() == "O"
+assert len(board.legal_actions()) == 8
+assert ttt.Board("XXX.OO...").winner() == "X"
+assert ttt.Board("X........") in ttt.Board("..X......").symmetries()
+try:
+    board.play(4)
+    raise AssertionError("played a taken field")
+except ValueError:
+    pass
+
+agent = ttt.Agent.load(path, seed=0)
+assert agent.kind == "table" and agent.memorized_boards == 1
+assert agent.choose_move(ttt.Board()) == 4
+assert agent.move_values(ttt.Board()) == {4: 6.0, 0: -3.0}
+assert agent.visits(ttt.Board())[4] == 2
+agent.epsilon = 0.5
+try:
+    agent.epsilon = 1.5
+    raise AssertionError("set an epsilon above 1")
+except ValueError:
+    assert agent.epsilon == 0.5
+
+env = ttt.Env("perfect", side="O", seed=0)
+observation = env.reset()
+assert env.side == "O" and len(observation["features"]) == 27
+done = False
+while not done:
+    action = observation["action_mask"].index(True)
+    observation, reward, done, info = env.step(action)
+assert reward <= 0 and info["state"] != "X to move"
+
+env = ttt.Env(agent, seed=0)
+observation = env.reset()
+assert env.step(0)[3]["opponent_action"] in observation["board"].after(0).legal_actions()
+"#,
+        )
+        .unwrap();
+        py.run(&code, Some(&globals), None).unwrap();
+    });
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,177 @@
+use crate::board::{Board, FieldPosition};
+use rand::distributions::{WeightedError, WeightedIndex};
+use rand::prelude::Distribution;
+use rand::Rng;
+use std::collections::VecDeque;
+
+/// One move of a learner with what followed it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Transition {
+    /// Saved board the move was played on.
+    pub board: Board,
+    /// Position of the move on the saved board.
+    pub action: FieldPosition,
+    /// Final reward for the last move of a game, zero otherwise.
+    pub reward: f64,
+    /// Saved board the learner moved on next, `None` if the game ended.
+    pub next_board: Option<Board>,
+}
+
+impl Transition {
+    pub fn is_terminal(&self) -> bool {
+        self.next_board.is_none()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ReplayConfig {
+    /// The oldest transitions are dropped once the buffer holds this many.
+    pub capacity: usize,
+    /// Replayed transitions per newly added one.
+    pub sample_ratio: f64,
+    /// Transitions are sampled proportional to their last error to the power of
+    /// this, `0` samples uniformly.
+    pub priority_exponent: f64,
+}
+
+impl Default for ReplayConfig {
+    fn default() -> Self {
+        Self {
+            capacity: 10_000,
+            sample_ratio: 1.0,
+            priority_exponent: 0.0,
+        }
+    }
+}
+
+/// Bounded buffer of past transitions to replay into a value table.
+///
+/// With prioritized sampling every transition has the size of the error of its
+/// last update as priority. New transitions get the highest known priority so
+/// they are replayed at least once early on.
+#[derive(Debug, Clone)]
+pub struct ReplayBuffer {
+    config: ReplayConfig,
+    transitions: VecDeque<Transition>,
+    priorities: VecDeque<f64>,
+    /// Transitions added since the last sample.
+    fresh: usize,
+}
+
+/// Keeps transitions whose error went to zero reachable by prioritized sampling.
+const MIN_PRIORITY: f64 = 1e-3;
+
+impl ReplayBuffer {
+    pub fn new(config: ReplayConfig) -> Self {
+        Self {
+            config,
+            transitions: VecDeque::with_capacity(config.capacity),
+            priorities: VecDeque::with_capacity(config.capacity),
+            fresh: 0,
+        }
+    }
+
+    pub fn config(&self) -> ReplayConfig {
+        self.config
+    }
+
+    pub fn len(&self) -> usize {
+        self.transitions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.transitions.is_empty()
+    }
+
+    pub fn push(&mut self, transition: Transition) {
+        if self.config.capacity == 0 {
+            return;
+        }
+        if self.transitions.len() == self.config.capacity {
+            self.transitions.pop_front();
+            self.priorities.pop_front();
+        }
+        let priority = self.priorities.iter().copied().fold(1.0, f64::max);
+        self.transitions.push_back(transition);
+        self.priorities.push_back(priority);
+        self.fresh += 1;
+    }
+
+    pub fn get(&self, index: usize) -> &Transition {
+        &self.transitions[index]
+    }
+
+    /// Sets the priority of a transition to the error of its latest update.
+    pub fn set_error(&mut self, index: usize, error: f64) {
+        self.priorities[index] = error.abs().max(MIN_PRIORITY);
+    }
+
+    /// Draws the indices of a minibatch with `sample_ratio` transitions per
+    /// transition added since the last call.
+    ///
+    /// Fails if the priority exponent is negative or not finite.
+    pub fn sample<R: Rng>(&mut self, rng: &mut R) -> Result<Vec<usize>, WeightedError> {
+        let count = (self.fresh as f64 * self.config.sample_ratio).round() as usize;
+        self.fresh = 0;
+        if self.transitions.is_empty() {
+            return Ok(Vec::new());
+        }
+        if self.config.priority_exponent == 0.0 {
+            return Ok((0..count)
+                .map(|_| rng.gen_range(0..self.transitions.len()))
+                .collect());
+        }
+        let exponent = self.config.priority_exponent;
+        if !exponent.is_finite() || exponent < 0.0 {
+            return Err(WeightedError::InvalidWeight);
+        }
+        // Relative to the highest priority the weights cannot overflow
+        let max_priority = self.priorities.iter().copied().fold(MIN_PRIORITY, f64::max);
+        let weights = self
+            .priorities
+            .iter()
+            .map(|priority| (priority / max_priority).powf(exponent));
+        let distribution = WeightedIndex::new(weights)?;
+        Ok((0..count).map(|_| distribution.sample(rng)).collect())
+    }
+}
+
+#[test]
+fn replay_buffer_test() {
+    use rand::{rngs::StdRng, SeedableRng};
+    let transition = |x| Transition {
+        board: Board::EMPTY,
+        action: FieldPosition::new(x, 0),
+        reward: 0.0,
+        next_board: None,
+    };
+    let mut buffer = ReplayBuffer::new(ReplayConfig {
+        capacity: 2,
+        sample_ratio: 2.0,
+        priority_exponent: 1.0,
+    });
+    for x in 0..3 {
+        buffer.push(transition(x));
+    }
+    // The first transition was dropped for the third one
+    assert_eq!(buffer.len(), 2);
+    assert_eq!(buffer.get(0), &transition(1));
+
+    buffer.set_error(0, 0.0);
+    buffer.set_error(1, -1000.0);
+    let mut rng = StdRng::seed_from_u64(0);
+    let sample = buffer.sample(&mut rng).unwrap();
+    assert_eq!(sample.len(), 6);
+    assert!(sample.iter().filter(|&&index| index == 1).count() >= 5);
+    // Nothing new was added since
+    assert!(buffer.sample(&mut rng).unwrap().is_empty());
+
+    // Priorities to the power of a huge exponent would overflow on their own
+    buffer.config.priority_exponent = 1e6;
+    buffer.push(transition(3));
+    buffer.set_error(0, 1.0);
+    assert_eq!(buffer.sample(&mut rng), Ok(vec![1, 1]));
+    buffer.config.priority_exponent = f64::NAN;
+    buffer.push(transition(4));
+    assert!(buffer.sample(&mut rng).is_err());
+}
@@ -0,0 +1,495 @@
+use crate::agent::{Agent, AgentAction, AgentConfig, Rewards};
+use crate::board::{Board, FieldPosition, FieldState};
+use crate::evaluation::{self, EvaluationResult};
+use crate::memory::BoardMemory;
+use crate::minimax::{Minimax, PerfectPlayer};
+use crate::player::{Player, RandomPlayer};
+use crate::training::{self, TrainingStats};
+use std::io::Write;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ReportConfig {
+    pub agent: AgentConfig,
+    pub rewards: Rewards,
+    /// Self-play training games.
+    pub games: usize,
+    /// Evaluations spread evenly over the training games.
+    pub checkpoints: usize,
+    /// Games per side against every evaluation opponent.
+    pub evaluation_games: usize,
+    pub seed: u64,
+}
+
+impl Default for ReportConfig {
+    fn default() -> Self {
+        Self {
+            agent: AgentConfig::default(),
+            rewards: Rewards::default(),
+            games: 50_000,
+            checkpoints: 25,
+            evaluation_games: 100,
+            seed: 0,
+        }
+    }
+}
+
+/// Evaluation of the agent pair partway through training.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Checkpoint {
+    /// Training games played so far.
+    pub games: usize,
+    /// Self-play results since the previous checkpoint.
+    pub training: TrainingStats,
+    /// Results of both sides against the perfect player.
+    pub vs_perfect: EvaluationResult,
+    /// Results of both sides against the random player.
+    pub vs_random: EvaluationResult,
+}
+
+/// A position where the agent's greedy move is worse than the minimax result.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mistake {
+    pub board: Board,
+    pub played: FieldPosition,
+    pub best_moves: Vec<FieldPosition>,
+    /// Finished games in which the agent moved on the board.
+    pub visits: u32,
+}
+
+/// Everything shown in the HTML training report.
+pub struct TrainingReport {
+    pub config: ReportConfig,
+    pub checkpoints: Vec<Checkpoint>,
+    /// Final results as X and as O against every evaluation opponent.
+    pub evaluation: Vec<(&'static str, EvaluationResult, EvaluationResult)>,
+    /// Both trained sides merged into one agent.
+    pub agent: Agent,
+    /// Mistakes of the trained agent, most visited positions first.
+    pub mistakes: Vec<Mistake>,
+}
+
+/// Trains an agent pair by self-play and evaluates it against the perfect and the
+/// random player at every checkpoint and once more at the end.
+pub fn run_training<F>(config: &ReportConfig, mut on_checkpoint: F) -> TrainingReport
+where
+    F: FnMut(&Checkpoint),
+{
+    let mut agent_cross = Agent::new_seeded(config.agent, config.seed);
+    let mut agent_circle = Agent::new_seeded(config.agent, config.seed.wrapping_add(1));
+    let mut perfect = PerfectPlayer::new_seeded(config.seed.wrapping_add(2));
+    let mut random = RandomPlayer::new_seeded(config.seed.wrapping_add(3));
+    let merged = |cross: &Agent, circle: &Agent| {
+        let mut agent = cross.clone();
+        agent.merge(circle);
+        agent
+    };
+
+    let checkpoints_count = config.checkpoints.max(1);
+    let mut checkpoints = Vec::with_capacity(checkpoints_count);
+    let mut games = 0;
+    for i in 0..checkpoints_count {
+        let until = config.games * (i + 1) / checkpoints_count;
+        let training = training::train_self_play(
+            &mut agent_cross,
+            &mut agent_circle,
+            config.rewards,
+            until - games,
+        );
+        games = until;
+        let mut agent = merged(&agent_cross, &agent_circle);
+        let mut both_sides = |opponent: &mut dyn Player| {
+            evaluation::evaluate_as_cross(&mut agent, opponent, config.evaluation_games)
+                + evaluation::evaluate_as_circle(
+                    &mut agent,
+                    opponent,
+                    config.evaluation_games,
+                )
+        };
+        let checkpoint = Checkpoint {
+            games,
+            training,
+            vs_perfect: both_sides(&mut perfect),
+            vs_random: both_sides(&mut random),
+        };
+        on_checkpoint(&checkpoint);
+        checkpoints.push(checkpoint);
+    }
+
+    let mut agent = merged(&agent_cross, &agent_circle);
+    let mut evaluation = Vec::new();
+    for (name, opponent) in [
+        ("perfect", &mut perfect as &mut dyn Player),
+        ("random", &mut random),
+    ] {
+        let games = config.evaluation_games;
+        let as_cross = evaluation::evaluate_as_cross(&mut agent, opponent, games);
+        let as_circle = evaluation::evaluate_as_circle(&mut agent, opponent, games);
+        evaluation.push((name, as_cross, as_circle));
+    }
+    let mistakes = find_mistakes(&agent);
+    TrainingReport {
+        config: *config,
+        checkpoints,
+        evaluation,
+        agent,
+        mistakes,
+    }
+}
+
+/// Finds every position, up to symmetry, on which the agent knows its moves and
+/// would greedily play a move that gives away a better minimax result.
+pub fn find_mistakes(agent: &Agent) -> Vec<Mistake> {
+    let mut minimax = Minimax::new();
+    let mut positions = BoardMemory::new();
+    let mut stack = vec![Board::EMPTY];
+    let mut mistakes = Vec::new();
+    while let Some(board) = stack.pop() {
+        if board.is_finished() || positions.find(&board).is_some() {
+            continue;
+        }
+        positions.insert(board.clone(), ());
+        for pos in board.get_empty_fields_pos() {
+            let mut next = board.clone();
+            next.play_move_at(pos);
+            stack.push(next);
+        }
+        let actions = match agent.get_actions_from_board(&board) {
+            Some(actions) => actions,
+            None => continue,
+        };
+        // The same choice as the agent's greedy play
+        let played = match actions.iter().max() {
+            Some(action) => action.field_pos,
+            None => continue,
+        };
+        let scores = minimax.move_scores(&board);
+        let best = scores.iter().map(|&(_, score)| score).max();
+        let played_score = scores.iter().find(|&&(pos, _)| pos == played);
+        if played_score.map(|&(_, score)| score) < best {
+            mistakes.push(Mistake {
+                best_moves: minimax.best_moves(&board),
+                board,
+                played,
+                visits: actions.iter().map(|action| action.visits).sum(),
+            });
+        }
+    }
+    mistakes.sort_by_key(|mistake| std::cmp::Reverse(mistake.visits));
+    mistakes
+}
+
+/// Mistakes drawn in the report, the rest are only counted.
+const SHOWN_MISTAKES: usize = 60;
+const CHART_WIDTH: f64 = 640.0;
+const CHART_HEIGHT: f64 = 280.0;
+const CHART_MARGIN: f64 = 40.0;
+const HEAT_COLOR: (u8, u8, u8) = (31, 111, 235);
+const PLAYED_COLOR: &str = "#f4a6a6";
+const BEST_COLOR: &str = "#a6e0b0";
+
+/// A line of the learning curve chart.
+struct Curve {
+    name: &'static str,
+    color: &'static str,
+    rate: fn(&Checkpoint) -> f64,
+}
+
+const CURVES: [Curve; 3] = [
+    Curve {
+        name: "not lost vs perfect",
+        color: "#1f6feb",
+        rate: |checkpoint| checkpoint.vs_perfect.non_loss_rate(),
+    },
+    Curve {
+        name: "won vs random",
+        color: "#2da44e",
+        rate: |checkpoint| {
+            let result = checkpoint.vs_random;
+            result.wins as f64 / result.games().max(1) as f64
+        },
+    },
+    Curve {
+        name: "self-play draws",
+        color: "#999999",
+        rate: |checkpoint| {
+            let stats = checkpoint.training;
+            let games = stats.cross_wins + stats.circle_wins + stats.draws;
+            stats.draws as f64 / games.max(1) as f64
+        },
+    },
+];
+
+impl TrainingReport {
+    /// Writes the report as a single HTML page with inline styles and SVG
+    /// graphics, so it opens in any browser without network access.
+    pub fn write_html<W: Write + ?Sized>(&self, out: &mut W) -> std::io::Result<()> {
+        let config = &self.config;
+        writeln!(out, "<!DOCTYPE html>")?;
+        writeln!(out, "<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">")?;
+        writeln!(out, "<title>Training report</title>")?;
+        writeln!(
+            out,
+            "<style>\n\
+             body {{ font-family: sans-serif; max-width: 60em; margin: 2em auto; }}\n\
+             table {{ border-collapse: collapse; }}\n\
+             th, td {{ border: 1px solid #ccc; padding: 0.3em 0.8em; text-align: right; }}\n\
+             figure {{ display: inline-block; margin: 0.5em; text-align: center; }}\n\
+             </style>\n</head>\n<body>"
+        )?;
+        writeln!(out, "<h1>Training report</h1>")?;
+        writeln!(
+            out,
+            "<p>{} self-play games with rewards {}/{}/{} (win/loss/draw), epsilon {} \
+             decaying by {} to at least {}, seed {}. The merged agent memorizes {} \
+             boards.</p>",
+            config.games,
+            config.rewards.win,
+            config.rewards.loss,
+            config.rewards.draw,
+            config.agent.epsilon,
+            config.agent.epsilon_decay,
+            config.agent.min_epsilon,
+            config.seed,
+            self.agent.memorized_boards_count()
+        )?;
+
+        writeln!(out, "<h2>Learning curves</h2>")?;
+        writeln!(
+            out,
+            "<p>Measured every {} games over {} games per side.</p>",
+            config.games / config.checkpoints.max(1),
+            config.evaluation_games
+        )?;
+        writeln!(out, "{}", self.curves_svg())?;
+
+        writeln!(out, "<h2>Final evaluation</h2>")?;
+        writeln!(
+            out,
+            "<table>\n<tr><th>opponent</th><th>as X (W/D/L)</th><th>as O (W/D/L)</th>\
+             <th>not lost</th></tr>"
+        )?;
+        for (name, as_cross, as_circle) in &self.evaluation {
+            writeln!(
+                out,
+                "<tr><td>{}</td><td>{}</td><td>{}</td><td>{:.1}%</td></tr>",
+                name,
+                as_cross,
+                as_circle,
+                (*as_cross + *as_circle).non_loss_rate() * 100.0
+            )?;
+        }
+        writeln!(out, "</table>")?;
+
+        writeln!(out, "<h2>First moves</h2>")?;
+        writeln!(
+            out,
+            "<p>Biases of the opening move and of the replies to a center, corner and \
+             edge opening, darker is preferred. Fields show bias and (visits).</p>"
+        )?;
+        let mut openings = vec![("X opens", Board::EMPTY)];
+        for (name, pos) in [
+            ("O answers the center", FieldPosition::new(1, 1)),
+            ("O answers a corner", FieldPosition::new(0, 0)),
+            ("O answers an edge", FieldPosition::new(1, 0)),
+        ] {
+            let mut board = Board::EMPTY;
+            board.play_move_at(pos);
+            openings.push((name, board));
+        }
+        for (name, board) in openings {
+            let actions = self.agent.get_actions_from_board(&board);
+            writeln!(
+                out,
+                "<figure>{}<figcaption>{}</figcaption></figure>",
+                heatmap_svg(&board, actions.as_deref().unwrap_or_default()),
+                name
+            )?;
+        }
+
+        writeln!(out, "<h2>Mistakes</h2>")?;
+        writeln!(
+            out,
+            "<p>{} positions where the greedy move, red, is worse than the best moves \
+             by minimax, green. Most visited first{}.</p>",
+            self.mistakes.len(),
+            if self.mistakes.len() > SHOWN_MISTAKES {
+                format!(", the first {} shown", SHOWN_MISTAKES)
+            } else {
+                String::new()
+            }
+        )?;
+        for mistake in self.mistakes.iter().take(SHOWN_MISTAKES) {
+            let mut fills =
+                vec![(mistake.played, PLAYED_COLOR.to_string(), String::new())];
+            for &pos in &mistake.best_moves {
+                fills.push((pos, BEST_COLOR.to_string(), String::new()));
+            }
+            writeln!(
+                out,
+                "<figure>{}<figcaption>{} to move, {} visits</figcaption></figure>",
+                board_svg(&mistake.board, &fills, 30.0),
+                mistake.board.next_player(),
+                mistake.visits
+            )?;
+        }
+        writeln!(out, "</body>\n</html>")
+    }
+
+    /// Line chart of the results at every checkpoint.
+    fn curves_svg(&self) -> String {
+        let plot_width = CHART_WIDTH - 2.0 * CHART_MARGIN;
+        let plot_height = CHART_HEIGHT - 2.0 * CHART_MARGIN;
+        let x = |games: usize| {
+            CHART_MARGIN + plot_width * games as f64 / self.config.games.max(1) as f64
+        };
+        let y = |rate: f64| CHART_MARGIN + plot_height * (1.0 - rate);
+        let mut svg = format!(
+            "<svg width=\"{}\" height=\"{}\" font-size=\"12\">\n",
+            CHART_WIDTH, CHART_HEIGHT
+        );
+        for percent in (0..=100).step_by(25) {
+            let y = y(percent as f64 / 100.0);
+            svg += &format!(
+                "<line x1=\"{}\" y1=\"{y:.1}\" x2=\"{}\" y2=\"{y:.1}\" stroke=\"#ddd\"/>\
+                 <text x=\"{}\" y=\"{:.1}\" text-anchor=\"end\">{}%</text>\n",
+                CHART_MARGIN,
+                CHART_WIDTH - CHART_MARGIN,
+                CHART_MARGIN - 4.0,
+                y + 4.0,
+                percent
+            );
+        }
+        svg += &format!(
+            "<text x=\"{}\" y=\"{}\" text-anchor=\"end\">{} games</text>\n",
+            CHART_WIDTH - CHART_MARGIN,
+            CHART_HEIGHT - CHART_MARGIN + 16.0,
+            self.config.games
+        );
+        for (i, curve) in CURVES.iter().enumerate() {
+            let points: Vec<String> = self
+                .checkpoints
+                .iter()
+                .map(|checkpoint| {
+                    let rate = (curve.rate)(checkpoint);
+                    format!("{:.1},{:.1}", x(checkpoint.games), y(rate))
+                })
+                .collect();
+            let color = curve.color;
+            let legend_x = CHART_MARGIN + 160.0 * i as f64;
+            svg += &format!(
+                "<polyline points=\"{}\" fill=\"none\" stroke=\"{color}\" \
+                 stroke-width=\"2\"/>\n\
+                 <rect x=\"{legend_x}\" y=\"10\" width=\"12\" height=\"12\" \
+                 fill=\"{color}\"/><text x=\"{}\" y=\"20\">{}</text>\n",
+                points.join(" "),
+                legend_x + 16.0,
+                curve.name
+            );
+        }
+        svg + "</svg>"
+    }
+}
+
+/// Draws a board with the known moves shaded by bias.
+fn heatmap_svg(board: &Board, actions: &[AgentAction]) -> String {
+    let min = actions.iter().map(|action| action.bias).min().unwrap_or(0);
+    let max = actions.iter().map(|action| action.bias).max().unwrap_or(0);
+    let fills: Vec<(FieldPosition, String, String)> = actions
+        .iter()
+        .map(|action| {
+            let heat = if max > min {
+                (action.bias - min) as f64 / (max - min) as f64
+            } else {
+                0.0
+            };
+            let channel = |full: u8| (255.0 - (255.0 - full as f64) * heat).round() as u8;
+            let (r, g, b) = HEAT_COLOR;
+            let fill = format!("#{:02x}{:02x}{:02x}", channel(r), channel(g), channel(b));
+            let text = format!("{:+} ({})", action.bias, action.visits);
+            (action.field_pos, fill, text)
+        })
+        .collect();
+    board_svg(board, &fills, 70.0)
+}
+
+/// Draws a board with the given fields filled and labeled.
+fn board_svg(
+    board: &Board,
+    fills: &[(FieldPosition, String, String)],
+    cell: f64,
+) -> String {
+    let size = cell * 3.0;
+    let mut svg = format!(
+        "<svg width=\"{size}\" height=\"{size}\" text-anchor=\"middle\" \
+         font-size=\"{:.0}\">\n",
+        cell / 6.0
+    );
+    for y in 0..3 {
+        for x in 0..3 {
+            let pos = FieldPosition::new(x, y);
+            let fill = fills.iter().find(|(other, _, _)| *other == pos);
+            let (left, top) = (x as f64 * cell, y as f64 * cell);
+            svg += &format!(
+                "<rect x=\"{left}\" y=\"{top}\" width=\"{cell}\" height=\"{cell}\" \
+                 fill=\"{}\" stroke=\"#333\"/>",
+                fill.map_or("#ffffff", |(_, color, _)| color)
+            );
+            let (center_x, center_y) = (left + cell / 2.0, top + cell / 2.0);
+            match board.0[y][x] {
+                FieldState::Empty => {
+                    if let Some((_, _, text)) =
+                        fill.filter(|(_, _, text)| !text.is_empty())
+                    {
+                        svg += &format!(
+                            "<text x=\"{center_x}\" y=\"{:.1}\">{}</text>",
+                            center_y + cell / 16.0,
+                            text
+                        );
+                    }
+                }
+                mark => {
+                    svg += &format!(
+                        "<text x=\"{center_x}\" y=\"{:.1}\" font-size=\"{:.0}\">{}</text>",
+                        center_y + cell / 6.0,
+                        cell / 2.0,
+                        mark
+                    );
+                }
+            }
+            svg += "\n";
+        }
+    }
+    svg + "</svg>"
+}
+
+#[test]
+fn report_test() {
+    // Answers the center with a corner, or with an edge which loses by force
+    let agent = Agent::read_from(
+        "agent 0 0.999 0.1\n\
+         ....X.... 0,0:1:1 1,0:5:3\n"
+            .as_bytes(),
+    )
+    .unwrap();
+    let mistakes = find_mistakes(&agent);
+    assert_eq!(mistakes.len(), 1);
+    assert_eq!(mistakes[0].played, FieldPosition::new(1, 0));
+    assert_eq!(mistakes[0].visits, 4);
+    assert!(mistakes[0].best_moves.contains(&FieldPosition::new(0, 0)));
+
+    let config = ReportConfig {
+        games: 200,
+        checkpoints: 4,
+        evaluation_games: 5,
+        ..Default::default()
+    };
+    let report = run_training(&config, |_| {});
+    assert_eq!(report.checkpoints.len(), 4);
+    assert_eq!(report.checkpoints[3].games, 200);
+    let mut html = Vec::new();
+    report.write_html(&mut html).unwrap();
+    let html = String::from_utf8(html).unwrap();
+    assert!(html.contains("<polyline"));
+    // Self-contained, nothing to fetch
+    assert!(!html.contains("http") && !html.contains("src="));
+}
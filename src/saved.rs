@@ -0,0 +1,87 @@
+use crate::agent::Agent;
+use crate::neural::NeuralAgent;
+use crate::player::{Learner, Player};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::Path;
+
+/// An agent loaded from a file, of either kind the binary can save.
+#[derive(Clone)]
+pub enum SavedAgent {
+    Table(Agent),
+    Neural(NeuralAgent),
+}
+
+impl SavedAgent {
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Self::read_from(BufReader::new(File::open(path)?))
+    }
+
+    /// Reads an agent written by [`Agent::write_to`] or [`NeuralAgent::write_to`],
+    /// telling them apart by the `neural` header.
+    pub fn read_from<R: BufRead>(mut reader: R) -> io::Result<Self> {
+        let mut content = String::new();
+        reader.read_to_string(&mut content)?;
+        if content.starts_with("neural ") {
+            NeuralAgent::read_from(content.as_bytes()).map(SavedAgent::Neural)
+        } else {
+            Agent::read_from(content.as_bytes()).map(SavedAgent::Table)
+        }
+    }
+
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        match self {
+            SavedAgent::Table(agent) => agent.write_to(writer),
+            SavedAgent::Neural(agent) => agent.write_to(writer),
+        }
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        match self {
+            SavedAgent::Table(agent) => agent.save(path),
+            SavedAgent::Neural(agent) => agent.save(path),
+        }
+    }
+
+    pub fn reseed(&mut self, seed: u64) {
+        match self {
+            SavedAgent::Table(agent) => agent.reseed(seed),
+            SavedAgent::Neural(agent) => agent.reseed(seed),
+        }
+    }
+
+    pub fn epsilon(&self) -> f64 {
+        match self {
+            SavedAgent::Table(agent) => agent.epsilon(),
+            SavedAgent::Neural(agent) => agent.epsilon(),
+        }
+    }
+
+    pub fn set_epsilon(&mut self, epsilon: f64) {
+        match self {
+            SavedAgent::Table(agent) => agent.set_epsilon(epsilon),
+            SavedAgent::Neural(agent) => agent.set_epsilon(epsilon),
+        }
+    }
+
+    pub fn discard_game(&mut self) {
+        match self {
+            SavedAgent::Table(agent) => agent.discard_game(),
+            SavedAgent::Neural(agent) => agent.discard_game(),
+        }
+    }
+
+    pub fn learner(&mut self) -> &mut dyn Learner {
+        match self {
+            SavedAgent::Table(agent) => agent,
+            SavedAgent::Neural(agent) => agent,
+        }
+    }
+
+    pub fn into_player(self) -> Box<dyn Player> {
+        match self {
+            SavedAgent::Table(agent) => Box::new(agent),
+            SavedAgent::Neural(agent) => Box::new(agent),
+        }
+    }
+}
@@ -0,0 +1,285 @@
+use crate::board::{Board, FieldPosition, FieldState};
+use crate::minimax::Minimax;
+use crate::saved::SavedAgent;
+use serde_json::{json, Value};
+use std::io;
+use tiny_http::{Header, Method, Request, Response, Server};
+
+/// Status code and JSON body of an answer.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ApiResponse {
+    pub status: u16,
+    pub body: Value,
+}
+
+impl ApiResponse {
+    fn ok(body: Value) -> Self {
+        Self { status: 200, body }
+    }
+
+    fn error(status: u16, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            body: json!({ "error": message.into() }),
+        }
+    }
+}
+
+/// JSON API over a saved agent, independent of the HTTP transport.
+///
+/// Boards are sent as `{"board": "X...O...."}`, nine fields row by row as written
+/// by [`Board::to_code`], and fields are returned as `{"x": 0, "y": 0, "field": 1}`
+/// with fields numbered 1 to 9 like the keys of the interactive commands.
+///
+/// - `POST /move` returns the agent's greedy move and the value of every move it
+///   knows: the bias and visit count for table agents, the network output for
+///   neural agents.
+/// - `POST /evaluate` returns the minimax value of the board for the player to
+///   move, its result under perfect play and the score of every move.
+/// - `GET /stats` returns the kind of agent, its exploration rate and the number
+///   of memorized boards.
+pub struct Api {
+    agent: SavedAgent,
+    minimax: Minimax,
+}
+
+impl Api {
+    pub fn new(agent: SavedAgent) -> Self {
+        Self {
+            agent,
+            minimax: Minimax::new(),
+        }
+    }
+
+    pub fn handle(&mut self, method: &str, path: &str, body: &str) -> ApiResponse {
+        let path = path.split('?').next().unwrap_or_default();
+        match (method, path) {
+            ("POST", "/move") => self.choose_move(body),
+            ("POST", "/evaluate") => self.evaluate(body),
+            ("GET", "/stats") => self.stats(),
+            (_, "/move" | "/evaluate" | "/stats") => {
+                ApiResponse::error(405, format!("{} is not allowed on {}", method, path))
+            }
+            _ => ApiResponse::error(404, format!("no endpoint {}", path)),
+        }
+    }
+
+    fn choose_move(&mut self, body: &str) -> ApiResponse {
+        let board = match parse_board(body) {
+            Ok(board) if board.is_finished() => {
+                return ApiResponse::error(422, "the game is over")
+            }
+            Ok(board) => board,
+            Err(err) => return ApiResponse::error(400, err),
+        };
+        let candidates: Vec<Value> = match &self.agent {
+            SavedAgent::Table(agent) => agent
+                .get_actions_from_board(&board)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|action| {
+                    let mut candidate = field_json(action.field_pos);
+                    candidate["value"] = json!(action.bias);
+                    candidate["visits"] = json!(action.visits);
+                    candidate
+                })
+                .collect(),
+            SavedAgent::Neural(agent) => agent
+                .move_values(&board)
+                .into_iter()
+                .map(|(pos, value)| {
+                    let mut candidate = field_json(pos);
+                    candidate["value"] = json!(value);
+                    candidate
+                })
+                .collect(),
+        };
+        let chosen = match self.agent.learner().choose_move(&board) {
+            Some(chosen) => chosen,
+            None => return ApiResponse::error(500, "the agent found no move"),
+        };
+        ApiResponse::ok(json!({
+            "board": board.to_code(),
+            "player": side_name(board.next_player()),
+            "move": field_json(chosen),
+            "candidates": candidates,
+        }))
+    }
+
+    fn evaluate(&mut self, body: &str) -> ApiResponse {
+        let board = match parse_board(body) {
+            Ok(board) => board,
+            Err(err) => return ApiResponse::error(400, err),
+        };
+        let value = self.minimax.evaluate(&board);
+        let result = match value {
+            0 => "draw",
+            value if value > 0 => "win",
+            _ => "loss",
+        };
+        let moves: Vec<Value> = self
+            .minimax
+            .move_scores(&board)
+            .into_iter()
+            .map(|(pos, score)| {
+                let mut field = field_json(pos);
+                field["value"] = json!(score);
+                field["best"] = json!(score == value);
+                field
+            })
+            .collect();
+        ApiResponse::ok(json!({
+            "board": board.to_code(),
+            "player": side_name(board.next_player()),
+            "finished": board.is_finished(),
+            "value": value,
+            "result": result,
+            "moves": moves,
+        }))
+    }
+
+    fn stats(&self) -> ApiResponse {
+        ApiResponse::ok(match &self.agent {
+            SavedAgent::Table(agent) => json!({
+                "kind": "table",
+                "memorized_boards": agent.memorized_boards_count(),
+                "shared_table": agent.has_shared_table(),
+                "epsilon": agent.epsilon(),
+            }),
+            SavedAgent::Neural(agent) => json!({
+                "kind": "neural",
+                "memorized_boards": Value::Null,
+                "epsilon": agent.epsilon(),
+            }),
+        })
+    }
+}
+
+fn parse_board(body: &str) -> Result<Board, String> {
+    let request: Value =
+        serde_json::from_str(body).map_err(|err| format!("invalid JSON: {}", err))?;
+    let code = request["board"]
+        .as_str()
+        .ok_or("expected a `board` string of nine X, O and . fields")?;
+    let board =
+        Board::from_code(code).ok_or_else(|| format!("invalid board `{}`", code))?;
+    let crosses = board.field_state_count(FieldState::X);
+    let circles = board.field_state_count(FieldState::O);
+    if crosses != circles && crosses != circles + 1 {
+        return Err(format!("board `{}` cannot occur, X always starts", code));
+    }
+    Ok(board)
+}
+
+fn field_json(pos: FieldPosition) -> Value {
+    json!({ "x": pos.x, "y": pos.y, "field": pos.y * 3 + pos.x + 1 })
+}
+
+fn side_name(side: FieldState) -> &'static str {
+    match side {
+        FieldState::O => "O",
+        _ => "X",
+    }
+}
+
+/// Answers requests to `server` until it is shut down. Every answer allows any
+/// origin so a web page served from elsewhere can call the API.
+pub fn serve(api: &mut Api, server: &Server) -> io::Result<()> {
+    for mut request in server.incoming_requests() {
+        let response = if *request.method() == Method::Options {
+            ApiResponse {
+                status: 204,
+                body: Value::Null,
+            }
+        } else {
+            let mut body = String::new();
+            match request.as_reader().read_to_string(&mut body) {
+                Ok(_) => api.handle(request.method().as_str(), request.url(), &body),
+                Err(err) => ApiResponse::error(400, err.to_string()),
+            }
+        };
+        respond(request, response)?;
+    }
+    Ok(())
+}
+
+fn respond(request: Request, response: ApiResponse) -> io::Result<()> {
+    let header = |name: &str, value: &str| {
+        Header::from_bytes(name.as_bytes(), value.as_bytes()).unwrap()
+    };
+    let body = match response.body {
+        Value::Null => String::new(),
+        body => body.to_string(),
+    };
+    let response = Response::from_string(body)
+        .with_status_code(response.status)
+        .with_header(header("Content-Type", "application/json"))
+        .with_header(header("Access-Control-Allow-Origin", "*"))
+        .with_header(header("Access-Control-Allow-Methods", "GET, POST, OPTIONS"))
+        .with_header(header("Access-Control-Allow-Headers", "Content-Type"));
+    request.respond(response)
+}
+
+#[test]
+fn api_test() {
+    use std::io::{Read, Write};
+    use std::net::TcpStream;
+
+    let agent = SavedAgent::read_from(
+        "agent 0 0.999 0.1\n\
+         ......... 1,1:6:2 0,0:-3:1\n"
+            .as_bytes(),
+    )
+    .unwrap();
+    let mut api = Api::new(agent);
+    let response = api.handle("POST", "/move", r#"{"board": "........."}"#);
+    assert_eq!(response.status, 200);
+    assert_eq!(response.body["move"]["field"], 5);
+    assert_eq!(response.body["candidates"].as_array().unwrap().len(), 2);
+    // X threatens the top row, O must block in the corner
+    let response = api.handle("POST", "/evaluate", r#"{"board": "XX..O...."}"#);
+    assert_eq!(response.body["player"], "O");
+    assert_eq!(response.body["result"], "draw");
+    let best: Vec<&Value> = response.body["moves"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter(|field| field["best"] == true)
+        .collect();
+    assert_eq!(best.len(), 1);
+    assert_eq!(best[0]["field"], 3);
+    assert_eq!(
+        api.handle("POST", "/move", r#"{"board": "XX......."}"#)
+            .status,
+        400
+    );
+    assert_eq!(api.handle("POST", "/move", "board").status, 400);
+    assert_eq!(api.handle("GET", "/move", "").status, 405);
+    assert_eq!(api.handle("GET", "/other", "").status, 404);
+
+    // The same API over HTTP on a free local port
+    let server = Server::http("127.0.0.1:0").unwrap();
+    let address = server.server_addr().to_ip().unwrap();
+    let handle = std::thread::spawn(move || {
+        let request = server.recv().unwrap();
+        let mut body = String::new();
+        let mut request = request;
+        request.as_reader().read_to_string(&mut body).unwrap();
+        let response = api.handle(request.method().as_str(), request.url(), &body);
+        respond(request, response).unwrap();
+    });
+    let mut stream = TcpStream::connect(address).unwrap();
+    write!(
+        stream,
+        "GET /stats HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n"
+    )
+    .unwrap();
+    let mut answer = String::new();
+    stream.read_to_string(&mut answer).unwrap();
+    handle.join().unwrap();
+    assert!(answer.starts_with("HTTP/1.1 200"));
+    let body: Value =
+        serde_json::from_str(answer.split("\r\n\r\n").nth(1).unwrap()).unwrap();
+    assert_eq!(body["kind"], "table");
+    assert_eq!(body["memorized_boards"], 1);
+}
@@ -0,0 +1,252 @@
+use crate::agent::{AgentConfig, Rewards};
+use crate::evaluation::{self, EvaluationResult};
+use crate::learners::{LearnerKind, LearnerParams};
+use crate::minimax::PerfectPlayer;
+use crate::td::TraceKind;
+use crate::training;
+use std::io::Write;
+use std::str::FromStr;
+
+/// Parameter grid of a hyperparameter sweep.
+///
+/// Every combination of the listed values trains its own pair of learners which
+/// are then evaluated against a [`PerfectPlayer`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct SweepConfig {
+    pub learner: LearnerKind,
+    pub win_rewards: Vec<i32>,
+    pub loss_rewards: Vec<i32>,
+    pub draw_rewards: Vec<i32>,
+    pub epsilons: Vec<f64>,
+    pub epsilon_decays: Vec<f64>,
+    pub min_epsilons: Vec<f64>,
+    /// Only used by the learners with a learning rate.
+    pub learning_rates: Vec<f64>,
+    /// Only used by the TD learners and the agent, `None` keeps their default.
+    pub lambdas: Vec<Option<f64>>,
+    pub trace_kind: TraceKind,
+    pub training_games: usize,
+    pub evaluation_games: usize,
+    pub seed: u64,
+}
+
+impl Default for SweepConfig {
+    fn default() -> Self {
+        let rewards = Rewards::default();
+        let config = AgentConfig::default();
+        let params = LearnerParams::default();
+        Self {
+            learner: LearnerKind::Agent,
+            win_rewards: vec![rewards.win],
+            loss_rewards: vec![rewards.loss],
+            draw_rewards: vec![rewards.draw],
+            epsilons: vec![config.epsilon],
+            epsilon_decays: vec![config.epsilon_decay],
+            min_epsilons: vec![config.min_epsilon],
+            learning_rates: vec![params.learning_rate],
+            lambdas: vec![params.lambda],
+            trace_kind: params.trace_kind,
+            training_games: 20_000,
+            evaluation_games: 100,
+            seed: 0,
+        }
+    }
+}
+
+impl SweepConfig {
+    pub fn combinations(&self) -> Vec<(Rewards, AgentConfig, LearnerParams)> {
+        let mut combinations = Vec::new();
+        for &win in &self.win_rewards {
+            for &loss in &self.loss_rewards {
+                for &draw in &self.draw_rewards {
+                    for &epsilon in &self.epsilons {
+                        for &epsilon_decay in &self.epsilon_decays {
+                            for &min_epsilon in &self.min_epsilons {
+                                let config = AgentConfig {
+                                    epsilon,
+                                    epsilon_decay,
+                                    min_epsilon,
+                                };
+                                for &learning_rate in &self.learning_rates {
+                                    for &lambda in &self.lambdas {
+                                        let params = LearnerParams {
+                                            learning_rate,
+                                            lambda,
+                                            trace_kind: self.trace_kind,
+                                            ..Default::default()
+                                        };
+                                        combinations.push((
+                                            Rewards { win, loss, draw },
+                                            config,
+                                            params,
+                                        ));
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        combinations
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SweepResult {
+    pub rewards: Rewards,
+    pub config: AgentConfig,
+    pub params: LearnerParams,
+    /// Results of the X agent against the perfect player.
+    pub as_cross: EvaluationResult,
+    /// Results of the O agent against the perfect player.
+    pub as_circle: EvaluationResult,
+}
+
+impl SweepResult {
+    pub fn total(&self) -> EvaluationResult {
+        self.as_cross + self.as_circle
+    }
+}
+
+/// Trains and evaluates every combination of the grid and returns the results
+/// ranked from best to worst. `on_progress` is called after each combination
+/// with its index and the total number of combinations.
+pub fn run_sweep<F>(sweep: &SweepConfig, mut on_progress: F) -> Vec<SweepResult>
+where
+    F: FnMut(usize, usize, &SweepResult),
+{
+    let combinations = sweep.combinations();
+    let mut results = Vec::with_capacity(combinations.len());
+    for (index, &(rewards, config, params)) in combinations.iter().enumerate() {
+        // Every combination gets its own seeds so single rows can be reproduced
+        let seed = sweep.seed.wrapping_add(3 * index as u64);
+        let mut agent_cross = sweep.learner.build(config, params, seed);
+        let mut agent_circle = sweep.learner.build(config, params, seed.wrapping_add(1));
+        let mut perfect = PerfectPlayer::new_seeded(seed.wrapping_add(2));
+        training::train_self_play(
+            agent_cross.as_mut(),
+            agent_circle.as_mut(),
+            rewards,
+            sweep.training_games,
+        );
+        let result = SweepResult {
+            rewards,
+            config,
+            params,
+            as_cross: evaluation::evaluate_as_cross(
+                agent_cross.as_mut(),
+                &mut perfect,
+                sweep.evaluation_games,
+            ),
+            as_circle: evaluation::evaluate_as_circle(
+                agent_circle.as_mut(),
+                &mut perfect,
+                sweep.evaluation_games,
+            ),
+        };
+        on_progress(index, combinations.len(), &result);
+        results.push(result);
+    }
+    // Fewest losses first, wins only break ties
+    results.sort_by(|a, b| {
+        let (a, b) = (a.total(), b.total());
+        a.losses.cmp(&b.losses).then(b.wins.cmp(&a.wins))
+    });
+    results
+}
+
+/// Formats the lambda of a result, `default` if the learner kept its own.
+pub fn lambda_label(lambda: Option<f64>) -> String {
+    lambda.map_or_else(|| "default".to_string(), |lambda| lambda.to_string())
+}
+
+/// Writes the ranked results as a plain text table.
+pub fn write_table<W: Write + ?Sized>(
+    results: &[SweepResult],
+    out: &mut W,
+) -> std::io::Result<()> {
+    writeln!(
+        out,
+        "{:>4} {:>5} {:>5} {:>5} {:>8} {:>8} {:>8} {:>6} {:>7} {:>14} {:>14} {:>10}",
+        "rank",
+        "win",
+        "loss",
+        "draw",
+        "epsilon",
+        "decay",
+        "min_eps",
+        "lr",
+        "lambda",
+        "X (W/D/L)",
+        "O (W/D/L)",
+        "non-loss"
+    )?;
+    for (rank, result) in results.iter().enumerate() {
+        writeln!(
+            out,
+            "{:>4} {:>5} {:>5} {:>5} {:>8} {:>8} {:>8} {:>6} {:>7} {:>14} {:>14} {:>9.1}%",
+            rank + 1,
+            result.rewards.win,
+            result.rewards.loss,
+            result.rewards.draw,
+            result.config.epsilon,
+            result.config.epsilon_decay,
+            result.config.min_epsilon,
+            result.params.learning_rate,
+            lambda_label(result.params.lambda),
+            result.as_cross.to_string(),
+            result.as_circle.to_string(),
+            result.total().non_loss_rate() * 100.0
+        )?;
+    }
+    Ok(())
+}
+
+/// Parses a parameter grid given either as a comma separated list (`1,2,5`)
+/// or as an inclusive range with a step (`0.1:0.5:0.1`).
+pub fn parse_grid<T>(input: &str) -> Result<Vec<T>, String>
+where
+    T: FromStr + Copy + PartialOrd + Into<f64> + std::ops::Add<Output = T>,
+{
+    let parse = |value: &str| {
+        value
+            .trim()
+            .parse::<T>()
+            .map_err(|_| format!("invalid value `{}` in `{}`", value, input))
+    };
+    let parts: Vec<&str> = input.split(':').collect();
+    match parts.as_slice() {
+        [list] => list.split(',').map(parse).collect(),
+        [start, end, step] => {
+            let (start, end, step) = (parse(start)?, parse(end)?, parse(step)?);
+            if start + step <= start {
+                return Err(format!("range `{}` needs a positive step", input));
+            }
+            if start > end {
+                return Err(format!("range `{}` starts after its end", input));
+            }
+            let mut values = vec![start];
+            let mut value = start + step;
+            // Allow for floating point error on the last step
+            while value.into() <= end.into() + 1e-9 {
+                values.push(value);
+                value = value + step;
+            }
+            Ok(values)
+        }
+        _ => Err(format!(
+            "`{}` is neither a list nor a start:end:step range",
+            input
+        )),
+    }
+}
+
+#[test]
+fn parse_grid_test() {
+    assert_eq!(parse_grid::<i32>("4,6,-8"), Ok(vec![4, 6, -8]));
+    assert_eq!(parse_grid::<i32>("1:7:3"), Ok(vec![1, 4, 7]));
+    assert_eq!(parse_grid::<f64>("0.1:0.3:0.1").unwrap().len(), 3);
+    assert!(parse_grid::<i32>("1:5:0").is_err());
+    assert!(parse_grid::<f64>("x").is_err());
+}
@@ -0,0 +1,471 @@
+use crate::agent::AgentConfig;
+use crate::board::{Board, FieldPosition};
+use crate::dyna::DynaModel;
+use crate::memory::BoardMemory;
+use crate::player::{Learner, Player};
+use crate::replay::{ReplayBuffer, ReplayConfig, Transition};
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+
+/// How a [`TdAgent`] estimates the value of the board it moves on next.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TdMethod {
+    /// Value of the best action, regardless of the action actually taken.
+    QLearning,
+    /// Value of the action actually taken next.
+    Sarsa,
+    /// Expected value under the epsilon-greedy exploration policy.
+    ExpectedSarsa,
+}
+
+impl std::fmt::Display for TdMethod {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TdMethod::QLearning => write!(f, "q-learning"),
+            TdMethod::Sarsa => write!(f, "sarsa"),
+            TdMethod::ExpectedSarsa => write!(f, "expected-sarsa"),
+        }
+    }
+}
+
+/// How eligibility traces of repeated actions combine.
+///
+/// Both kinds give identical updates in tic-tac-toe: the boards one side moves on
+/// in a game all have different numbers of marks, so no saved entry is revisited.
+/// They are kept for parity with the textbook algorithm.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TraceKind {
+    /// Every visit adds one to the trace.
+    Accumulating,
+    /// Every visit resets the trace to one.
+    Replacing,
+}
+
+impl std::str::FromStr for TraceKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "accumulating" => Ok(TraceKind::Accumulating),
+            "replacing" => Ok(TraceKind::Replacing),
+            _ => Err(format!(
+                "unknown trace `{}`, expected accumulating or replacing",
+                s
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ActionValue {
+    pub field_pos: FieldPosition,
+    pub value: f64,
+}
+
+/// Agent learning action values with temporal difference updates.
+///
+/// Each of its moves is updated as soon as the agent moves again, towards the
+/// value of the board it then faces as estimated by the [`TdMethod`]. The last
+/// move of a game is updated towards the final reward.
+///
+/// With eligibility traces (TD(λ)) every error also updates the earlier moves of
+/// the game, weighted by `lambda` to the power of their distance. `lambda = 0` is
+/// one-step TD and `lambda = 1` moves every action towards the final reward like
+/// Monte Carlo. Q-learning cuts the traces after exploring moves (Watkins's Q(λ)).
+///
+/// With experience replay the agent also keeps the transitions of past games
+/// and replays a minibatch of them after every game. Replayed transitions are
+/// updated one step towards the best next action regardless of the method, as
+/// the actions taken next belong to an older policy.
+///
+/// With planning (Dyna-Q) the agent learns a [`DynaModel`] of the outcomes of its
+/// moves and makes a number of simulated one-step updates from it after every
+/// real move.
+#[derive(Clone)]
+pub struct TdAgent {
+    method: TdMethod,
+    action_values: BoardMemory<Vec<ActionValue>>,
+    epsilon: f64,
+    epsilon_decay: f64,
+    min_epsilon: f64,
+    learning_rate: f64,
+    rng: StdRng,
+    /// Saved board and saved position of the last move waiting for its update.
+    pending: Option<(Board, FieldPosition)>,
+    lambda: f64,
+    trace_kind: TraceKind,
+    /// Eligibility traces of this game's moves by saved board and position.
+    traces: Vec<(Board, FieldPosition, f64)>,
+    replay: Option<ReplayBuffer>,
+    model: Option<DynaModel>,
+    planning_steps: usize,
+}
+
+impl TdAgent {
+    pub fn new(method: TdMethod, config: AgentConfig, learning_rate: f64) -> Self {
+        Self::with_rng(method, config, learning_rate, StdRng::from_entropy())
+    }
+
+    pub fn new_seeded(
+        method: TdMethod,
+        config: AgentConfig,
+        learning_rate: f64,
+        seed: u64,
+    ) -> Self {
+        Self::with_rng(method, config, learning_rate, StdRng::seed_from_u64(seed))
+    }
+
+    fn with_rng(
+        method: TdMethod,
+        config: AgentConfig,
+        learning_rate: f64,
+        rng: StdRng,
+    ) -> Self {
+        Self {
+            method,
+            action_values: BoardMemory::new(),
+            epsilon: config.epsilon,
+            epsilon_decay: config.epsilon_decay,
+            min_epsilon: config.min_epsilon,
+            learning_rate,
+            rng,
+            pending: None,
+            lambda: 0.0,
+            trace_kind: TraceKind::Replacing,
+            traces: Vec::with_capacity(5),
+            replay: None,
+            model: None,
+            planning_steps: 0,
+        }
+    }
+
+    /// Spreads every update over the earlier moves of the game, see [`TdAgent`].
+    pub fn with_traces(mut self, lambda: f64, trace_kind: TraceKind) -> Self {
+        self.lambda = lambda.clamp(0.0, 1.0);
+        self.trace_kind = trace_kind;
+        self
+    }
+
+    /// Replays past transitions after every game, see [`TdAgent`].
+    pub fn with_replay(mut self, config: ReplayConfig) -> Self {
+        self.replay = Some(ReplayBuffer::new(config));
+        self
+    }
+
+    /// Plans `steps` simulated updates per real move, see [`TdAgent`].
+    pub fn with_planning(mut self, steps: usize) -> Self {
+        self.model = Some(DynaModel::new());
+        self.planning_steps = steps;
+        self
+    }
+
+    pub fn model(&self) -> Option<&DynaModel> {
+        self.model.as_ref()
+    }
+
+    pub fn replay_buffer(&self) -> Option<&ReplayBuffer> {
+        self.replay.as_ref()
+    }
+
+    pub fn lambda(&self) -> f64 {
+        self.lambda
+    }
+
+    pub fn method(&self) -> TdMethod {
+        self.method
+    }
+
+    /// Returns the known action values of a board in its original orientation.
+    pub fn get_action_values(&self, board: &Board) -> Option<Vec<ActionValue>> {
+        let (saved_board, transformation) = self.action_values.find(board)?;
+        self.action_values.get(&saved_board).map(|actions| {
+            actions
+                .iter()
+                .map(|action| ActionValue {
+                    field_pos: transformation.pos_to_original(action.field_pos),
+                    value: action.value,
+                })
+                .collect()
+        })
+    }
+
+    pub fn memorized_boards_count(&self) -> usize {
+        self.action_values.len()
+    }
+
+    pub fn epsilon(&self) -> f64 {
+        self.epsilon
+    }
+
+    fn update_pending(&mut self, target: f64) {
+        let (board, pos) = match self.pending.take() {
+            Some(pending) => pending,
+            None => return,
+        };
+        let error = target - self.action_value_mut(&board, pos).value;
+        match self
+            .traces
+            .iter_mut()
+            .find(|(b, p, _)| *b == board && *p == pos)
+        {
+            Some((_, _, trace)) => match self.trace_kind {
+                TraceKind::Accumulating => *trace += 1.0,
+                TraceKind::Replacing => *trace = 1.0,
+            },
+            None => self.traces.push((board, pos, 1.0)),
+        }
+        let mut traces = std::mem::take(&mut self.traces);
+        for (board, pos, trace) in &mut traces {
+            let step = self.learning_rate * error * *trace;
+            self.action_value_mut(board, *pos).value += step;
+            *trace *= self.lambda;
+        }
+        traces.retain(|&(_, _, trace)| trace > 1e-6);
+        self.traces = traces;
+    }
+
+    /// Remembers the pending move for replay and planning, with what followed.
+    fn record_transition(&mut self, reward: f64, next_board: Option<&Board>) {
+        if self.replay.is_none() && self.model.is_none() {
+            return;
+        }
+        let (board, pos) = match &self.pending {
+            Some(pending) => pending,
+            None => return,
+        };
+        let transition = Transition {
+            board: board.clone(),
+            action: *pos,
+            reward,
+            next_board: next_board.cloned(),
+        };
+        if let Some(model) = &mut self.model {
+            model.record(&transition);
+        }
+        if let Some(replay) = &mut self.replay {
+            replay.push(transition);
+        }
+    }
+
+    /// Moves the value of a past transition one step towards its reward or the
+    /// best value of the board that followed. Returns the error before the step.
+    fn backup(&mut self, transition: &Transition) -> f64 {
+        let target = match &transition.next_board {
+            Some(next) => max_value(self.action_values.get(next).unwrap()),
+            None => transition.reward,
+        };
+        let learning_rate = self.learning_rate;
+        let action = self.action_value_mut(&transition.board, transition.action);
+        let error = target - action.value;
+        action.value += learning_rate * error;
+        error
+    }
+
+    fn plan(&mut self) {
+        let model = match self.model.take() {
+            Some(model) => model,
+            None => return,
+        };
+        for _ in 0..self.planning_steps {
+            if let Some(transition) = model.sample(&mut self.rng) {
+                self.backup(&transition);
+            }
+        }
+        self.model = Some(model);
+    }
+
+    fn replay_minibatch(&mut self) {
+        let mut replay = match self.replay.take() {
+            Some(replay) => replay,
+            None => return,
+        };
+        // Unusable priorities skip the replay instead of stopping the training
+        let indices = replay.sample(&mut self.rng).unwrap_or_default();
+        for index in indices {
+            let error = self.backup(replay.get(index));
+            replay.set_error(index, error);
+        }
+        self.replay = Some(replay);
+    }
+
+    fn action_value_mut(
+        &mut self,
+        saved_board: &Board,
+        pos: FieldPosition,
+    ) -> &mut ActionValue {
+        self.action_values
+            .get_mut(saved_board)
+            .unwrap()
+            .iter_mut()
+            .find(|action| action.field_pos == pos)
+            .unwrap()
+    }
+
+    /// Value of a saved board as estimated by the agent's method, given the
+    /// action chosen on it.
+    fn board_value(&self, actions: &[ActionValue], chosen: FieldPosition) -> f64 {
+        let best = max_value(actions);
+        match self.method {
+            TdMethod::QLearning => best,
+            TdMethod::Sarsa => {
+                actions
+                    .iter()
+                    .find(|action| action.field_pos == chosen)
+                    .unwrap()
+                    .value
+            }
+            TdMethod::ExpectedSarsa => {
+                let greedy_count = actions.iter().filter(|a| a.value == best).count();
+                let explore = self.epsilon / actions.len() as f64;
+                let exploit = (1.0 - self.epsilon) / greedy_count as f64;
+                actions
+                    .iter()
+                    .map(|action| {
+                        let probability = if action.value == best {
+                            explore + exploit
+                        } else {
+                            explore
+                        };
+                        probability * action.value
+                    })
+                    .sum()
+            }
+        }
+    }
+}
+
+pub(crate) fn max_value(actions: &[ActionValue]) -> f64 {
+    actions
+        .iter()
+        .map(|action| action.value)
+        .max_by(f64::total_cmp)
+        .unwrap_or(0.0)
+}
+
+/// Picks a random action among the ones with the highest value.
+pub(crate) fn greedy_action(
+    actions: &[ActionValue],
+    rng: &mut StdRng,
+) -> Option<FieldPosition> {
+    let best = max_value(actions);
+    let best_actions: Vec<FieldPosition> = actions
+        .iter()
+        .filter(|action| action.value == best)
+        .map(|action| action.field_pos)
+        .collect();
+    best_actions.choose(rng).copied()
+}
+
+impl Player for TdAgent {
+    fn choose_move(&mut self, board: &Board) -> Option<FieldPosition> {
+        match self.get_action_values(board) {
+            Some(actions) => greedy_action(&actions, &mut self.rng),
+            None => board.get_empty_fields_pos().choose(&mut self.rng).copied(),
+        }
+    }
+}
+
+impl Learner for TdAgent {
+    fn play_training_move(&mut self, board: &Board) -> Option<FieldPosition> {
+        if board.is_finished() {
+            return None;
+        }
+        let (saved_board, transformation) =
+            self.action_values.find_or_insert_with(board, || {
+                board
+                    .get_empty_fields_pos()
+                    .into_iter()
+                    .map(|field_pos| ActionValue {
+                        field_pos,
+                        value: 0.0,
+                    })
+                    .collect()
+            });
+        let actions = self.action_values.get(&saved_board).unwrap().clone();
+        let chosen = if self.rng.gen_bool(self.epsilon) {
+            actions.choose(&mut self.rng).map(|action| action.field_pos)
+        } else {
+            greedy_action(&actions, &mut self.rng)
+        }?;
+
+        let target = self.board_value(&actions, chosen);
+        self.record_transition(0.0, Some(&saved_board));
+        self.update_pending(target);
+        let explored = actions.iter().any(|action| {
+            action.field_pos == chosen && action.value < max_value(&actions)
+        });
+        if self.method == TdMethod::QLearning && explored {
+            self.traces.clear();
+        }
+        self.pending = Some((saved_board, chosen));
+        self.plan();
+        Some(transformation.pos_to_original(chosen))
+    }
+
+    fn give_feedback(&mut self, reward: i32) {
+        if reward > 0 {
+            self.epsilon = (self.epsilon * self.epsilon_decay).max(self.min_epsilon);
+        }
+        self.record_transition(reward as f64, None);
+        self.update_pending(reward as f64);
+        self.traces.clear();
+        self.plan();
+        self.replay_minibatch();
+    }
+}
+
+#[test]
+fn td_board_value_test() {
+    let config = AgentConfig {
+        epsilon: 0.5,
+        ..Default::default()
+    };
+    let actions = [
+        ActionValue {
+            field_pos: FieldPosition::new(0, 0),
+            value: 4.0,
+        },
+        ActionValue {
+            field_pos: FieldPosition::new(1, 0),
+            value: 0.0,
+        },
+    ];
+    let chosen = FieldPosition::new(1, 0);
+    let value = |method| {
+        TdAgent::new_seeded(method, config, 0.1, 0).board_value(&actions, chosen)
+    };
+    assert_eq!(value(TdMethod::QLearning), 4.0);
+    assert_eq!(value(TdMethod::Sarsa), 0.0);
+    // The greedy action is taken with probability 0.5 + 0.5 / 2
+    assert_eq!(value(TdMethod::ExpectedSarsa), 3.0);
+}
+
+#[test]
+fn td_lambda_test() {
+    let config = AgentConfig {
+        epsilon: 0.0,
+        ..Default::default()
+    };
+    // Plays two moves and gets a reward of 8, returns the first move's value
+    let first_move_value = |lambda| {
+        let mut agent = TdAgent::new_seeded(TdMethod::Sarsa, config, 0.5, 0)
+            .with_traces(lambda, TraceKind::Replacing);
+        let first = agent.play_training_move(&Board::EMPTY).unwrap();
+        let mut board = Board::EMPTY;
+        board.play_move_at(first);
+        board.play_move_at(board.get_empty_fields_pos()[0]);
+        agent.play_training_move(&board).unwrap();
+        agent.give_feedback(8);
+        agent
+            .get_action_values(&Board::EMPTY)
+            .unwrap()
+            .into_iter()
+            .find(|action| action.field_pos == first)
+            .unwrap()
+            .value
+    };
+    // One-step TD only updates the last move, the first one stays at zero
+    assert_eq!(first_move_value(0.0), 0.0);
+    // The final error of 8 moves the last move by 4 and the first one by 4 * lambda
+    assert_eq!(first_move_value(0.5), 2.0);
+    assert_eq!(first_move_value(1.0), 4.0);
+}
@@ -0,0 +1,178 @@
+use crate::evaluation::{self, EvaluationResult};
+use crate::player::Player;
+use std::io::Write;
+
+/// A named player taking part in a tournament.
+pub struct Entrant {
+    pub name: String,
+    pub player: Box<dyn Player>,
+}
+
+impl Entrant {
+    pub fn new<S: Into<String>>(name: S, player: Box<dyn Player>) -> Self {
+        Self {
+            name: name.into(),
+            player,
+        }
+    }
+}
+
+pub struct TournamentResult {
+    pub names: Vec<String>,
+    /// `crosstable[i][j]` holds the results of entrant `i` against entrant `j`,
+    /// summed over both colors.
+    pub crosstable: Vec<Vec<EvaluationResult>>,
+    pub ratings: Vec<f64>,
+}
+
+/// Plays every pairing of entrants `games_per_color` times with each entrant as X
+/// and as O, then fits Elo ratings to the results.
+pub fn run_round_robin(
+    entrants: &mut [Entrant],
+    games_per_color: usize,
+) -> TournamentResult {
+    let count = entrants.len();
+    let mut crosstable = vec![vec![EvaluationResult::default(); count]; count];
+    let pairings = (0..count).flat_map(|i| (0..count).map(move |j| (i, j)));
+    for (i, j) in pairings.filter(|(i, j)| i != j) {
+        let (cross, circle) = pair_mut(entrants, i, j);
+        let result = evaluation::evaluate_as_cross(
+            cross.player.as_mut(),
+            circle.player.as_mut(),
+            games_per_color,
+        );
+        crosstable[i][j] = crosstable[i][j] + result;
+        crosstable[j][i] = crosstable[j][i] + result.opponent_view();
+    }
+    let ratings = elo_ratings(&crosstable);
+    TournamentResult {
+        names: entrants.iter().map(|e| e.name.clone()).collect(),
+        crosstable,
+        ratings,
+    }
+}
+
+fn pair_mut<T>(items: &mut [T], i: usize, j: usize) -> (&mut T, &mut T) {
+    if i < j {
+        let (left, right) = items.split_at_mut(j);
+        (&mut left[i], &mut right[0])
+    } else {
+        let (left, right) = items.split_at_mut(i);
+        (&mut right[0], &mut left[j])
+    }
+}
+
+/// Fits Elo ratings to a crosstable so that every entrant's expected score
+/// matches its actual score. Ratings are centered around 1500.
+///
+/// Every pairing gets one virtual draw so entrants which won or lost all their
+/// games still end up with finite ratings.
+pub fn elo_ratings(crosstable: &[Vec<EvaluationResult>]) -> Vec<f64> {
+    let count = crosstable.len();
+    let mut ratings = vec![0.0; count];
+    for _ in 0..2000 {
+        let mut next = ratings.clone();
+        for i in 0..count {
+            let mut actual = 0.0;
+            let mut expected = 0.0;
+            let mut games = 0.0;
+            for j in (0..count).filter(|&j| j != i) {
+                let result = &crosstable[i][j];
+                let n = result.games() as f64 + 1.0;
+                actual += result.wins as f64 + 0.5 * (result.draws as f64 + 1.0);
+                expected += n / (1.0 + 10f64.powf((ratings[j] - ratings[i]) / 400.0));
+                games += n;
+            }
+            if games > 0.0 {
+                next[i] += 400.0 * (actual - expected) / games;
+            }
+        }
+        ratings = next;
+    }
+    let mean = ratings.iter().sum::<f64>() / count.max(1) as f64;
+    ratings
+        .iter()
+        .map(|rating| rating - mean + 1500.0)
+        .collect()
+}
+
+impl TournamentResult {
+    /// Writes the entrants ranked by rating, followed by the crosstable.
+    /// Crosstable cells are `W/D/L` of the row entrant against the column entrant.
+    pub fn write_report<W: Write + ?Sized>(&self, out: &mut W) -> std::io::Result<()> {
+        let width = self.names.iter().map(String::len).max().unwrap_or(0).max(4);
+        let mut ranking: Vec<usize> = (0..self.names.len()).collect();
+        ranking.sort_by(|&a, &b| self.ratings[b].total_cmp(&self.ratings[a]));
+
+        writeln!(
+            out,
+            "{:>4}  {:<width$} {:>6} {:>14} {:>7}",
+            "rank", "name", "elo", "W/D/L", "score"
+        )?;
+        for (rank, &i) in ranking.iter().enumerate() {
+            let total = self.crosstable[i]
+                .iter()
+                .fold(EvaluationResult::default(), |sum, &result| sum + result);
+            writeln!(
+                out,
+                "{:>4}  {:<width$} {:>6.0} {:>14} {:>6.1}%",
+                rank + 1,
+                self.names[i],
+                self.ratings[i],
+                total.to_string(),
+                total.score() * 100.0
+            )?;
+        }
+
+        writeln!(out)?;
+        write!(out, "{:<width$}", "")?;
+        for &j in &ranking {
+            write!(out, " {:>12}", truncate(&self.names[j], 12))?;
+        }
+        writeln!(out)?;
+        for &i in &ranking {
+            write!(out, "{:<width$}", self.names[i])?;
+            for &j in &ranking {
+                if i == j {
+                    write!(out, " {:>12}", "-")?;
+                } else {
+                    write!(out, " {:>12}", self.crosstable[i][j].to_string())?;
+                }
+            }
+            writeln!(out)?;
+        }
+        Ok(())
+    }
+}
+
+fn truncate(name: &str, len: usize) -> &str {
+    match name.char_indices().nth(len) {
+        Some((i, _)) => &name[..i],
+        None => name,
+    }
+}
+
+#[test]
+fn elo_ratings_test() {
+    let result = |wins, draws, losses| EvaluationResult {
+        wins,
+        draws,
+        losses,
+    };
+    let crosstable = vec![
+        vec![result(0, 0, 0), result(8, 2, 0), result(10, 0, 0)],
+        vec![result(0, 2, 8), result(0, 0, 0), result(5, 5, 0)],
+        vec![result(0, 0, 10), result(0, 5, 5), result(0, 0, 0)],
+    ];
+    let ratings = elo_ratings(&crosstable);
+    assert!(ratings[0] > ratings[1] && ratings[1] > ratings[2]);
+    assert!((ratings.iter().sum::<f64>() / 3.0 - 1500.0).abs() < 1e-6);
+
+    // Even results give equal ratings
+    let crosstable = vec![
+        vec![result(0, 0, 0), result(3, 4, 3)],
+        vec![result(3, 4, 3), result(0, 0, 0)],
+    ];
+    let ratings = elo_ratings(&crosstable);
+    assert!((ratings[0] - ratings[1]).abs() < 1e-6);
+}
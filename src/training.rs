@@ -0,0 +1,141 @@
+use crate::agent::Rewards;
+use crate::board::FieldState;
+use crate::evaluation::EvaluationResult;
+use crate::game::{GameEvent, GameState};
+use crate::player::{Learner, Player, SharedLearner};
+
+/// Results of a self-play training run, counted from X's point of view.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct TrainingStats {
+    pub cross_wins: usize,
+    pub circle_wins: usize,
+    pub draws: usize,
+}
+
+/// Lets two agents play `games` games against each other, rewarding both
+/// after every game.
+pub fn train_self_play(
+    agent_cross: &mut dyn Learner,
+    agent_circle: &mut dyn Learner,
+    rewards: Rewards,
+    games: usize,
+) -> TrainingStats {
+    let game = GameState::new();
+    let mut stats = TrainingStats::default();
+    for _ in 0..games {
+        game.run_new(|event, board_state| match event {
+            GameEvent::CrossWon => {
+                agent_cross.give_feedback(rewards.win);
+                agent_circle.give_feedback(rewards.loss);
+                stats.cross_wins += 1;
+            }
+            GameEvent::CircleWon => {
+                agent_cross.give_feedback(rewards.loss);
+                agent_circle.give_feedback(rewards.win);
+                stats.circle_wins += 1;
+            }
+            GameEvent::Draw => {
+                agent_cross.give_feedback(rewards.draw);
+                agent_circle.give_feedback(rewards.draw);
+                stats.draws += 1;
+            }
+            GameEvent::CrossTurn => {
+                let position = agent_cross.play_training_move(board_state);
+                board_state.play_move_at(position.unwrap());
+            }
+            GameEvent::CircleTurn => {
+                let position = agent_circle.play_training_move(board_state);
+                board_state.play_move_at(position.unwrap());
+            }
+            GameEvent::InvalidBoard => unreachable!(),
+        })
+    }
+    stats
+}
+
+/// Lets a shared-table agent play both sides of `games` games, learning from the
+/// moves of X and of O after every game.
+pub fn train_shared_self_play(
+    agent: &mut dyn SharedLearner,
+    rewards: Rewards,
+    games: usize,
+) -> TrainingStats {
+    let game = GameState::new();
+    let mut stats = TrainingStats::default();
+    for _ in 0..games {
+        game.run_new(|event, board_state| match event {
+            GameEvent::CrossWon => {
+                agent.give_feedback_per_side(rewards.win, rewards.loss);
+                stats.cross_wins += 1;
+            }
+            GameEvent::CircleWon => {
+                agent.give_feedback_per_side(rewards.loss, rewards.win);
+                stats.circle_wins += 1;
+            }
+            GameEvent::Draw => {
+                agent.give_feedback_per_side(rewards.draw, rewards.draw);
+                stats.draws += 1;
+            }
+            GameEvent::CrossTurn | GameEvent::CircleTurn => {
+                let position = agent.play_training_move(board_state);
+                board_state.play_move_at(position.unwrap());
+            }
+            GameEvent::InvalidBoard => unreachable!(),
+        })
+    }
+    stats
+}
+
+/// Trains a single agent against a fixed `opponent`, switching sides every game
+/// so the agent learns to play both X and O. Returns the agent's results.
+pub fn train_against(
+    agent: &mut dyn Learner,
+    opponent: &mut dyn Player,
+    rewards: Rewards,
+    games: usize,
+) -> EvaluationResult {
+    let mut result = EvaluationResult::default();
+    for i in 0..games {
+        let agent_side = if i % 2 == 0 {
+            FieldState::X
+        } else {
+            FieldState::O
+        };
+        let winner = train_game(agent, opponent, rewards, agent_side);
+        result.record_winner(winner, agent_side);
+    }
+    result
+}
+
+/// Plays one training game with the agent on `agent_side` and returns the winner,
+/// [`FieldState::Empty`] for a draw.
+pub fn train_game(
+    agent: &mut dyn Learner,
+    opponent: &mut dyn Player,
+    rewards: Rewards,
+    agent_side: FieldState,
+) -> FieldState {
+    let mut winner = FieldState::Empty;
+    GameState::new().run_new(|event, board_state| match event {
+        GameEvent::CrossWon | GameEvent::CircleWon | GameEvent::Draw => {
+            winner = board_state.find_winner();
+            if winner == FieldState::Empty {
+                agent.give_feedback(rewards.draw);
+            } else if winner == agent_side {
+                agent.give_feedback(rewards.win);
+            } else {
+                agent.give_feedback(rewards.loss);
+            }
+        }
+        GameEvent::CrossTurn | GameEvent::CircleTurn => {
+            let position = if board_state.next_player() == agent_side {
+                agent.play_training_move(board_state)
+            } else {
+                opponent.choose_move(board_state)
+            };
+            board_state.play_move_at(position.unwrap());
+        }
+        GameEvent::InvalidBoard => unreachable!(),
+    });
+    winner
+}
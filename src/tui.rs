@@ -0,0 +1,485 @@
+use crate::board::{Board, FieldPosition, FieldState};
+use crate::player::Player;
+use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use crossterm::style::{Attribute, Color, Print, SetAttribute, SetForegroundColor};
+use crossterm::{cursor, execute, queue, terminal};
+use std::io::{self, Write};
+use std::time::{Duration, Instant};
+
+/// Values of the legal moves of a board, shown over the empty fields.
+pub type MoveValues = Box<dyn FnMut(&Board) -> Vec<(FieldPosition, f64)>>;
+
+/// One side of a game in the terminal UI: a human at the keyboard or a player
+/// moving by itself.
+pub struct Seat {
+    name: String,
+    player: Option<Box<dyn Player>>,
+    values: Option<MoveValues>,
+}
+
+impl Seat {
+    pub fn human() -> Self {
+        Self {
+            name: "you".to_string(),
+            player: None,
+            values: None,
+        }
+    }
+
+    pub fn computer(name: impl Into<String>, player: Box<dyn Player>) -> Self {
+        Self {
+            name: name.into(),
+            player: Some(player),
+            values: None,
+        }
+    }
+
+    /// Lets the value overlay show what the player thinks of the moves.
+    pub fn with_values<F>(mut self, values: F) -> Self
+    where
+        F: FnMut(&Board) -> Vec<(FieldPosition, f64)> + 'static,
+    {
+        self.values = Some(Box::new(values));
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn is_human(&self) -> bool {
+        self.player.is_none()
+    }
+}
+
+const MIN_DELAY: Duration = Duration::from_millis(50);
+const MAX_DELAY: Duration = Duration::from_millis(3200);
+const CELL_WIDTH: usize = 7;
+const KEYS: &str = "arrows/1-9 pick  enter play  space pause  s step  +/- speed  \
+                    v values  u undo  r new game  q quit";
+
+/// State of the terminal UI, separate from the terminal so it can be driven by
+/// key codes alone.
+pub struct App {
+    seats: [Seat; 2],
+    board: Board,
+    moves: Vec<FieldPosition>,
+    cursor: FieldPosition,
+    /// Whether computer seats move by themselves after `delay`.
+    auto_play: bool,
+    delay: Duration,
+    show_values: bool,
+    /// Wins of X and O and draws over all finished games.
+    score: [usize; 3],
+    message: String,
+    quit: bool,
+}
+
+impl App {
+    pub fn new(cross: Seat, circle: Seat) -> Self {
+        Self {
+            seats: [cross, circle],
+            board: Board::EMPTY,
+            moves: Vec::with_capacity(9),
+            cursor: FieldPosition::new(1, 1),
+            auto_play: true,
+            delay: Duration::from_millis(500),
+            show_values: true,
+            score: [0; 3],
+            message: String::new(),
+            quit: false,
+        }
+    }
+
+    pub fn with_delay(mut self, delay: Duration) -> Self {
+        self.delay = delay.clamp(MIN_DELAY, MAX_DELAY);
+        self
+    }
+
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    pub fn moves(&self) -> &[FieldPosition] {
+        &self.moves
+    }
+
+    fn seat_to_move(&mut self) -> Option<&mut Seat> {
+        if self.board.is_finished() {
+            return None;
+        }
+        let index = match self.board.next_player() {
+            FieldState::O => 1,
+            _ => 0,
+        };
+        Some(&mut self.seats[index])
+    }
+
+    /// Whether a computer seat is to move and should do so without a key press.
+    pub fn wants_computer_move(&mut self) -> bool {
+        let auto_play = self.auto_play;
+        self.seat_to_move()
+            .is_some_and(|seat| !seat.is_human() && auto_play)
+    }
+
+    /// Whether a finished game should restart by itself, when nobody plays along.
+    pub fn wants_restart(&self) -> bool {
+        self.auto_play
+            && self.board.is_finished()
+            && self.seats.iter().all(|s| !s.is_human())
+    }
+
+    pub fn delay(&self) -> Duration {
+        self.delay
+    }
+
+    pub fn should_quit(&self) -> bool {
+        self.quit
+    }
+
+    fn play(&mut self, pos: FieldPosition) {
+        self.board.play_move_at(pos);
+        self.moves.push(pos);
+        self.message.clear();
+        if self.board.is_finished() {
+            let winner = self.board.find_winner();
+            let (index, message) = match winner {
+                FieldState::X => (0, format!("X ({}) wins", self.seats[0].name)),
+                FieldState::O => (1, format!("O ({}) wins", self.seats[1].name)),
+                FieldState::Empty => (2, "draw".to_string()),
+            };
+            self.score[index] += 1;
+            self.message = message;
+        }
+    }
+
+    /// Lets the computer seat to move pick its move. Does nothing on a human's
+    /// turn or a finished board.
+    pub fn computer_move(&mut self) {
+        let board = self.board.clone();
+        let pos = match self.seat_to_move() {
+            Some(Seat {
+                player: Some(player),
+                ..
+            }) => player.choose_move(&board),
+            _ => return,
+        };
+        if let Some(pos) = pos {
+            self.play(pos);
+        }
+    }
+
+    pub fn new_game(&mut self) {
+        self.board = Board::EMPTY;
+        self.moves.clear();
+        self.message.clear();
+    }
+
+    /// Takes back moves up to and including the last one of a human, or the
+    /// last move if nobody plays along.
+    fn undo(&mut self) {
+        if self.board.is_finished() {
+            return self.message = "the game is over, r starts a new one".to_string();
+        }
+        let any_human = self.seats.iter().any(Seat::is_human);
+        while let Some(pos) = self.moves.pop() {
+            self.board.0[pos.y][pos.x] = FieldState::Empty;
+            let seat = &self.seats[self.moves.len() % 2];
+            if seat.is_human() || !any_human {
+                break;
+            }
+        }
+        self.message.clear();
+    }
+
+    pub fn handle_key(&mut self, key: KeyCode) {
+        let (x, y) = (self.cursor.x, self.cursor.y);
+        match key {
+            KeyCode::Char('q') | KeyCode::Esc => self.quit = true,
+            KeyCode::Left | KeyCode::Char('h') => self.cursor.x = x.saturating_sub(1),
+            KeyCode::Right | KeyCode::Char('l') => self.cursor.x = (x + 1).min(2),
+            KeyCode::Up | KeyCode::Char('k') => self.cursor.y = y.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => self.cursor.y = (y + 1).min(2),
+            KeyCode::Char(digit @ '1'..='9') => {
+                let n = digit as usize - '1' as usize;
+                self.cursor = FieldPosition::new(n % 3, n / 3);
+                self.human_move();
+            }
+            KeyCode::Enter => {
+                if self.board.is_finished() {
+                    self.new_game();
+                } else {
+                    self.human_move();
+                }
+            }
+            KeyCode::Char(' ') => self.auto_play = !self.auto_play,
+            KeyCode::Char('s') => self.computer_move(),
+            KeyCode::Char('+') => self.delay = (self.delay / 2).max(MIN_DELAY),
+            KeyCode::Char('-') => self.delay = (self.delay * 2).min(MAX_DELAY),
+            KeyCode::Char('v') => self.show_values = !self.show_values,
+            KeyCode::Char('u') => self.undo(),
+            KeyCode::Char('r') => self.new_game(),
+            _ => (),
+        }
+    }
+
+    /// Plays the field under the cursor if a human is to move.
+    fn human_move(&mut self) {
+        let pos = self.cursor;
+        match self.seat_to_move() {
+            Some(seat) if seat.is_human() => {
+                if self.board.0[pos.y][pos.x] == FieldState::Empty {
+                    self.play(pos);
+                } else {
+                    self.message = "that field is taken".to_string();
+                }
+            }
+            Some(_) => self.message = "wait for the computer, or press s".to_string(),
+            None => (),
+        }
+    }
+
+    /// Values to show over the empty fields, from the seat to move or else the
+    /// first seat that has any.
+    fn overlay(&mut self) -> Option<(String, Vec<(FieldPosition, f64)>)> {
+        if !self.show_values || self.board.is_finished() {
+            return None;
+        }
+        let board = self.board.clone();
+        let to_move = match board.next_player() {
+            FieldState::O => 1,
+            _ => 0,
+        };
+        for index in [to_move, 1 - to_move] {
+            let seat = &mut self.seats[index];
+            if let Some(values) = &mut seat.values {
+                let values = values(&board);
+                if !values.is_empty() {
+                    return Some((seat.name.clone(), values));
+                }
+            }
+        }
+        None
+    }
+
+    /// Draws the whole screen.
+    pub fn render<W: Write>(&mut self, out: &mut W) -> io::Result<()> {
+        let overlay = self.overlay();
+        queue!(
+            out,
+            terminal::Clear(terminal::ClearType::All),
+            cursor::MoveTo(0, 0)
+        )?;
+        let line =
+            |out: &mut W, text: &str| queue!(out, Print(text), cursor::MoveToNextLine(1));
+        line(
+            out,
+            &format!(
+                " X: {}    O: {}    score X {} / O {} / draws {}",
+                self.seats[0].name,
+                self.seats[1].name,
+                self.score[0],
+                self.score[1],
+                self.score[2]
+            ),
+        )?;
+        line(out, "")?;
+
+        let best = overlay.as_ref().and_then(|(_, values)| {
+            values
+                .iter()
+                .map(|&(_, value)| value)
+                .max_by(f64::total_cmp)
+        });
+        let border = |left: &str, middle: &str, right: &str| {
+            format!(
+                "   {}{}{}",
+                left,
+                vec!["─".repeat(CELL_WIDTH); 3].join(middle),
+                right
+            )
+        };
+        line(out, &border("┌", "┬", "┐"))?;
+        for y in 0..3 {
+            for row in 0..3 {
+                queue!(out, Print("   │"))?;
+                for x in 0..3 {
+                    let pos = FieldPosition::new(x, y);
+                    let field = self.board.0[y][x];
+                    let value = overlay.as_ref().and_then(|(_, values)| {
+                        values
+                            .iter()
+                            .find(|&&(other, _)| other == pos)
+                            .map(|&(_, v)| v)
+                    });
+                    let text = match (row, field, value) {
+                        (1, FieldState::Empty, Some(value)) => format_value(value),
+                        (1, FieldState::Empty, None) => String::new(),
+                        (1, field, _) => field.to_string(),
+                        (2, FieldState::Empty, _) => format!("{}", y * 3 + x + 1),
+                        _ => String::new(),
+                    };
+                    let color = match (field, value) {
+                        (FieldState::X, _) => Color::Red,
+                        (FieldState::O, _) => Color::Blue,
+                        (FieldState::Empty, Some(value)) if Some(value) == best => {
+                            Color::Green
+                        }
+                        _ => Color::DarkGrey,
+                    };
+                    let last_move = self.moves.last() == Some(&pos);
+                    if pos == self.cursor {
+                        queue!(out, SetAttribute(Attribute::Reverse))?;
+                    }
+                    if last_move && row == 1 {
+                        queue!(out, SetAttribute(Attribute::Bold))?;
+                    }
+                    queue!(
+                        out,
+                        SetForegroundColor(color),
+                        Print(format!("{:^width$}", text, width = CELL_WIDTH)),
+                        SetAttribute(Attribute::Reset),
+                        Print("│")
+                    )?;
+                }
+                queue!(out, cursor::MoveToNextLine(1))?;
+            }
+            if y < 2 {
+                line(out, &border("├", "┼", "┤"))?;
+            }
+        }
+        line(out, &border("└", "┴", "┘"))?;
+        line(out, "")?;
+
+        let moves: Vec<String> = self
+            .moves
+            .iter()
+            .enumerate()
+            .map(|(i, pos)| {
+                let side = if i % 2 == 0 { 'X' } else { 'O' };
+                format!("{}. {} {}", i + 1, side, pos.y * 3 + pos.x + 1)
+            })
+            .collect();
+        line(out, &format!(" moves: {}", moves.join("  ")))?;
+        let message = self.message.clone();
+        let status = match self.seat_to_move() {
+            _ if !message.is_empty() => message,
+            None => String::new(),
+            Some(seat) if seat.is_human() => "your move".to_string(),
+            Some(seat) => format!("{} is thinking", seat.name),
+        };
+        let status = if self.board.is_finished() {
+            format!("{}, enter or r for a new game", status)
+        } else {
+            format!("{} to move: {}", self.board.next_player(), status)
+        };
+        line(out, &format!(" {}", status))?;
+        line(
+            out,
+            &format!(
+                " auto-play {}, {} ms per move",
+                if self.auto_play { "on" } else { "paused" },
+                self.delay.as_millis()
+            ),
+        )?;
+        match &overlay {
+            Some((name, _)) => line(out, &format!(" values of {}, best in green", name))?,
+            None if self.show_values => line(out, " no values for this board")?,
+            None => line(out, " values hidden")?,
+        }
+        line(out, "")?;
+        line(out, &format!(" {}", KEYS))?;
+        out.flush()
+    }
+}
+
+/// Fits a value into a cell.
+fn format_value(value: f64) -> String {
+    if value.abs() >= 100.0 || value.fract() == 0.0 {
+        format!("{:+.0}", value)
+    } else {
+        format!("{:+.2}", value)
+    }
+}
+
+/// Puts the terminal back into its normal state when dropped.
+struct TerminalGuard;
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        let _ = execute!(io::stdout(), terminal::LeaveAlternateScreen, cursor::Show);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+/// Runs the app full-screen until the user quits.
+pub fn run(mut app: App) -> io::Result<()> {
+    terminal::enable_raw_mode()?;
+    let _guard = TerminalGuard;
+    let mut stdout = io::stdout();
+    execute!(stdout, terminal::EnterAlternateScreen, cursor::Hide)?;
+    let mut last_move = Instant::now();
+    while !app.should_quit() {
+        app.render(&mut stdout)?;
+        let waiting = app.wants_computer_move() || app.wants_restart();
+        let timeout = if waiting {
+            app.delay().saturating_sub(last_move.elapsed())
+        } else {
+            Duration::from_secs(60)
+        };
+        if event::poll(timeout)? {
+            if let Event::Key(key) = event::read()? {
+                if key.kind == KeyEventKind::Press {
+                    app.handle_key(key.code);
+                    last_move = Instant::now();
+                }
+            }
+        } else if app.wants_computer_move() {
+            app.computer_move();
+            last_move = Instant::now();
+        } else if app.wants_restart() {
+            app.new_game();
+            last_move = Instant::now();
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn app_test() {
+    use crate::minimax::PerfectPlayer;
+
+    let perfect = Box::new(PerfectPlayer::new_seeded(0));
+    let mut app = App::new(Seat::human(), Seat::computer("perfect", perfect));
+    // The computer waits for the human
+    assert!(!app.wants_computer_move());
+    app.handle_key(KeyCode::Enter);
+    assert_eq!(app.moves(), &[FieldPosition::new(1, 1)]);
+    assert!(app.wants_computer_move());
+    app.computer_move();
+    assert_eq!(app.moves().len(), 2);
+    // Taken fields are refused, undo takes back the computer's move as well
+    let taken = app.moves()[1];
+    app.cursor = taken;
+    app.handle_key(KeyCode::Enter);
+    assert_eq!(app.moves().len(), 2);
+    app.handle_key(KeyCode::Char('u'));
+    assert_eq!(app.board(), &Board::EMPTY);
+
+    let mut screen = Vec::new();
+    app.render(&mut screen).unwrap();
+    assert!(String::from_utf8_lossy(&screen).contains("your move"));
+
+    // Two computers play a whole game one step at a time
+    let mut app = App::new(
+        Seat::computer("perfect", Box::new(PerfectPlayer::new_seeded(1))),
+        Seat::computer("perfect", Box::new(PerfectPlayer::new_seeded(2))),
+    );
+    app.handle_key(KeyCode::Char(' '));
+    assert!(!app.wants_computer_move());
+    for _ in 0..9 {
+        app.handle_key(KeyCode::Char('s'));
+    }
+    assert!(app.board().is_finished());
+    assert_eq!(app.board().find_winner(), FieldState::Empty);
+}
@@ -0,0 +1,153 @@
+use crate::board::{Board, FieldPosition, FieldState};
+use crate::game::GameEvent;
+use crate::player::Player;
+use crate::saved::SavedAgent;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use wasm_bindgen::prelude::*;
+
+/// Agent of the browser demo, trained with `train --table shared --opponent hard
+/// --games 300000 --seed 1`.
+const DEMO_AGENT: &str = include_str!("../web/demo.agent");
+
+/// A game against a saved agent for the browser, with fields numbered 0 to 8 row by
+/// row and boards written as nine `X`, `O` and `.` characters.
+///
+/// The agent plays greedily and only breaks ties between equally good moves at
+/// random, seeded from JavaScript or from `crypto.getRandomValues`.
+#[wasm_bindgen]
+pub struct Game {
+    board: Board,
+    agent: Box<dyn Player>,
+}
+
+#[wasm_bindgen]
+impl Game {
+    /// Starts a game against the embedded demo agent.
+    #[wasm_bindgen(constructor)]
+    pub fn new(seed: Option<u32>) -> Game {
+        Self::load(DEMO_AGENT, seed).expect("the demo agent is valid")
+    }
+
+    /// Starts a game against an agent saved by the binary, given as the content of
+    /// its file.
+    #[wasm_bindgen(js_name = withAgent)]
+    pub fn with_agent(agent: &str, seed: Option<u32>) -> Result<Game, JsError> {
+        Self::load(agent, seed).map_err(|err| JsError::new(&err))
+    }
+
+    /// Clears the board, X moves first.
+    #[wasm_bindgen(js_name = newGame)]
+    pub fn new_game(&mut self) {
+        self.board = Board::EMPTY;
+    }
+
+    /// Plays `field` for the side to move.
+    pub fn play(&mut self, field: usize) -> Result<(), JsError> {
+        self.play_field(field).map_err(|err| JsError::new(&err))
+    }
+
+    /// Plays the agent's move for the side to move and returns its field, or
+    /// `undefined` once the game is over.
+    #[wasm_bindgen(js_name = aiMove)]
+    pub fn ai_move(&mut self) -> Option<usize> {
+        if self.board.is_finished() {
+            return None;
+        }
+        let pos = self.agent.choose_move(&self.board)?;
+        self.board.play_move_at(pos);
+        Some(pos.y * 3 + pos.x)
+    }
+
+    pub fn board(&self) -> String {
+        self.board.to_code()
+    }
+
+    /// `"X won"`, `"O won"`, `"draw"`, `"X to move"` or `"O to move"`.
+    pub fn state(&self) -> String {
+        GameEvent::of(&self.board).to_string()
+    }
+
+    #[wasm_bindgen(js_name = isFinished)]
+    pub fn is_finished(&self) -> bool {
+        self.board.is_finished()
+    }
+
+    #[wasm_bindgen(js_name = legalMoves)]
+    pub fn legal_moves(&self) -> Vec<usize> {
+        if self.board.is_finished() {
+            return Vec::new();
+        }
+        let empty_fields = self.board.get_empty_fields_pos();
+        empty_fields
+            .into_iter()
+            .map(|pos| pos.y * 3 + pos.x)
+            .collect()
+    }
+}
+
+impl Game {
+    fn load(agent: &str, seed: Option<u32>) -> Result<Game, String> {
+        let mut agent = SavedAgent::read_from(agent.as_bytes())
+            .map_err(|err| format!("invalid agent: {}", err))?;
+        let seed = match seed {
+            Some(seed) => seed as u64,
+            None => StdRng::from_entropy().gen(),
+        };
+        agent.reseed(seed);
+        Ok(Game {
+            board: Board::EMPTY,
+            agent: agent.into_player(),
+        })
+    }
+
+    fn play_field(&mut self, field: usize) -> Result<(), String> {
+        if field > 8 {
+            return Err(format!("invalid field {}", field));
+        }
+        if self.board.is_finished() {
+            return Err("the game is over".to_string());
+        }
+        let pos = FieldPosition::new(field % 3, field / 3);
+        if self.board.0[pos.y][pos.x] != FieldState::Empty {
+            return Err(format!("field {} is taken", field));
+        }
+        self.board.play_move_at(pos);
+        Ok(())
+    }
+}
+
+#[test]
+fn game_test() {
+    let mut game = Game::new(Some(0));
+    assert_eq!(game.legal_moves().len(), 9);
+    // The demo agent does not lose to a human always taking the first free field
+    while !game.is_finished() {
+        let field = game.legal_moves()[0];
+        game.play(field).unwrap();
+        if let Some(field) = game.ai_move() {
+            assert!(!game.legal_moves().contains(&field));
+        }
+    }
+    assert_ne!(game.state(), "X won");
+    assert_eq!(game.ai_move(), None);
+    assert!(game.play_field(4).is_err());
+
+    game.new_game();
+    game.play_field(4).unwrap();
+    assert_eq!(game.play_field(4), Err("field 4 is taken".to_string()));
+    assert_eq!(game.board(), "....X....");
+    assert!(Game::load("nonsense", Some(0)).is_err());
+}
+
+/// Runs in a headless wasm runner with `wasm-pack test --node -- --lib
+/// --no-default-features --features wasm`, seeding from `crypto.getRandomValues`.
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen_test::wasm_bindgen_test]
+fn game_wasm_test() {
+    let mut game = Game::new(None);
+    game.play(4).unwrap();
+    let field = game.ai_move().unwrap();
+    assert_ne!(field, 4);
+    assert!(game.play(field).is_err());
+    assert!(Game::with_agent("nonsense", Some(0)).is_err());
+}